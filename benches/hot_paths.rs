@@ -0,0 +1,218 @@
+// Benchmarks of the hot paths of the heuristic, on instances generated from a
+// fixed seed. Run with `cargo bench`, or `cargo bench --features f32` for
+// single-precision distances.
+
+use criterion::{
+  black_box, criterion_group, criterion_main, BenchmarkId, Criterion,
+  Throughput,
+};
+use std::io::Write;
+
+use maxmeandp_vlns::alns::Alns;
+use maxmeandp_vlns::greedy;
+use maxmeandp_vlns::instance::Instance;
+use maxmeandp_vlns::options::{Options, Parser};
+use maxmeandp_vlns::solution::Solution;
+use maxmeandp_vlns::trace::Trace;
+use maxmeandp_vlns::util::{TabuConfig, TabuList, Timer};
+use maxmeandp_vlns::vlns::{self, SubinstanceBuffers};
+
+const SEED: u64 = 1;
+const SIZES: [usize; 2] = [500, 2000];
+const ALPHAS: [f64; 4] = [0.0, 0.2, 0.5, 1.0];
+
+/// Default options, as given on the command line
+fn options() -> Options {
+  Options::parse_from(["maxmeandp-vlns", "-i", "bench"])
+}
+
+fn instance(n: usize) -> Instance {
+  fastrand::seed(SEED);
+  Instance::random(n)
+}
+
+/// A local optimum, as found by the multistart of the VLNS
+fn solution<'a>(inst: &'a Instance, opt: &Options) -> Solution<'a> {
+  fastrand::seed(SEED);
+  vlns::initial_solution(inst, 0, opt)
+}
+
+fn read_from_file(c: &mut Criterion) {
+  let mut g = c.benchmark_group("read_from_file");
+  g.sample_size(10);
+  for n in SIZES {
+    let inst = instance(n);
+    let path = std::env::temp_dir().join(format!("maxmeandp-bench-{}.txt", n));
+    let mut f = std::io::BufWriter::new(std::fs::File::create(&path).unwrap());
+    for i in 0..n {
+      for j in (i + 1)..n {
+        writeln!(f, "{} {} {}", i + 1, j + 1, inst.dist(i, j)).unwrap();
+      }
+    }
+    f.flush().unwrap();
+    g.throughput(Throughput::Elements((n * (n - 1) / 2) as u64));
+    g.bench_function(BenchmarkId::from_parameter(n), |b| {
+      b.iter(|| Instance::read_from_file(path.to_str().unwrap()))
+    });
+    std::fs::remove_file(&path).unwrap();
+  }
+  g.finish();
+}
+
+fn recompute_from_v(c: &mut Criterion) {
+  let opt = options();
+  let mut g = c.benchmark_group("recompute_from_v");
+  for n in SIZES {
+    let inst = instance(n);
+    let mut s = solution(&inst, &opt);
+    g.throughput(Throughput::Elements((s.len * n) as u64));
+    g.bench_function(BenchmarkId::from_parameter(n), |b| {
+      b.iter(|| s.recompute_from_v())
+    });
+  }
+  g.finish();
+}
+
+fn ts(c: &mut Criterion) {
+  let opt = options();
+  let mut g = c.benchmark_group("ts");
+  g.sample_size(10);
+  for n in SIZES {
+    let inst = instance(n);
+    g.bench_function(BenchmarkId::from_parameter(n), |b| {
+      b.iter(|| {
+        // from a single vertex to a local optimum, as in the multistart
+        let mut s = Solution::new(&inst);
+        s.add(0);
+        greedy::ts(
+          &inst,
+          &mut s,
+          TabuConfig::fixed(0),
+          0,
+          greedy::Neighborhood::from_options(&opt),
+          None,
+          None,
+        );
+        s
+      })
+    });
+  }
+  g.finish();
+}
+
+fn add_shadow(c: &mut Criterion) {
+  let mut g = c.benchmark_group("add_shadow");
+  for n in SIZES {
+    let inst = instance(n);
+    let mut s = Solution::new(&inst);
+    g.throughput(Throughput::Elements(n as u64));
+    g.bench_function(BenchmarkId::from_parameter(n), |b| {
+      let mut u = 0;
+      b.iter(|| {
+        // add and remove, so that costs stay bounded
+        for f in [Solution::add_shadow, Solution::remove_shadow] {
+          f(
+            black_box(u),
+            &inst,
+            &mut s.has,
+            &mut s.cost,
+            &mut s.total_cost,
+            &mut s.len,
+          );
+        }
+        u = (u + 1) % n;
+      })
+    });
+  }
+  g.finish();
+}
+
+fn orders(c: &mut Criterion) {
+  let opt = options();
+  let mut g = c.benchmark_group("orders");
+  for n in SIZES {
+    let inst = instance(n);
+    let s = solution(&inst, &opt);
+    let sz = opt.subp_sz / 2;
+    for alpha in ALPHAS {
+      let p = format!("{}/{}", n, alpha);
+      g.bench_function(BenchmarkId::new("insertion_order", &p), |b| {
+        b.iter(|| greedy::insertion_order(&inst, &s, sz, alpha, None))
+      });
+      g.bench_function(BenchmarkId::new("removal_order", &p), |b| {
+        b.iter(|| greedy::removal_order(&inst, &s, sz, alpha, None))
+      });
+    }
+  }
+  g.finish();
+}
+
+fn create_subinstance(c: &mut Criterion) {
+  let opt = options();
+  let mut g = c.benchmark_group("create_subinstance");
+  for n in SIZES {
+    let inst = instance(n);
+    let s = solution(&inst, &opt);
+    let tabu = TabuList::new(inst.n, TabuConfig::vlns(&opt));
+    let mut buf = SubinstanceBuffers::new(&inst);
+    let rlx = Alns::new(&opt).choose();
+    g.bench_function(BenchmarkId::from_parameter(n), |b| {
+      b.iter(|| {
+        let mut core_cost = 0.0;
+        vlns::create_subinstance(
+          &inst,
+          &s,
+          &rlx,
+          opt.subp_alpha,
+          &tabu,
+          &mut buf,
+          &mut core_cost,
+        )
+      })
+    });
+  }
+  g.finish();
+}
+
+fn vlns_iter(c: &mut Criterion) {
+  let opt = options();
+  let mut g = c.benchmark_group("vlns_iter");
+  g.sample_size(10);
+  for n in SIZES {
+    let inst = instance(n);
+    let s = solution(&inst, &opt);
+    let tabu = TabuList::new(inst.n, TabuConfig::vlns(&opt));
+    let mut buf = SubinstanceBuffers::new(&inst);
+    let mut alns = Alns::new(&opt);
+    let mut trace = Trace::disabled();
+    let timer = Timer::new(opt.time_limit);
+    g.bench_function(BenchmarkId::from_parameter(n), |b| {
+      b.iter(|| {
+        vlns::vlns_iter(
+          &inst,
+          &s,
+          alns.choose(),
+          opt.subp_alpha,
+          &tabu,
+          &opt,
+          &mut buf,
+          &mut trace,
+          &timer,
+        )
+      })
+    });
+  }
+  g.finish();
+}
+
+criterion_group!(
+  benches,
+  read_from_file,
+  recompute_from_v,
+  ts,
+  add_shadow,
+  orders,
+  create_subinstance,
+  vlns_iter
+);
+criterion_main!(benches);
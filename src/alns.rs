@@ -0,0 +1,308 @@
+// Adaptive selection of the VLNS neighborhoods (ALNS-style): relaxation
+// operators and subproblem sizes are chosen by roulette wheels whose weights
+// follow the improvements each choice produced.
+
+use crate::greedy;
+use crate::instance::Instance;
+use crate::options::{Options, RelaxOp};
+use crate::solution::Solution;
+use crate::util::TabuList;
+
+/// Minimum weight of a choice, so that every choice can still be selected
+const MIN_WEIGHT: f64 = 0.1;
+/// Scores of a choice that found a new incumbent, or improved the current
+/// solution
+const SCORE_INC: f64 = 3.0;
+const SCORE_IMPROVED: f64 = 1.0;
+
+/// A neighborhood of the VLNS: how many vertices are relaxed in and out of the
+/// solution, and how they are chosen
+#[derive(Clone, Copy, Debug)]
+pub struct Relaxation {
+  pub op: RelaxOp,
+  pub sz_in: usize,
+  pub sz_out: usize,
+}
+
+impl Relaxation {
+  /// Grows the relaxation by --subp-growth per iteration without improvement,
+  /// keeping its in/out ratio, up to --subp-sz-max vertices
+  pub fn grown(self, iters_wo_impr: usize, opt: &Options) -> Relaxation {
+    if opt.subp_growth <= 0.0 || iters_wo_impr == 0 {
+      return self;
+    }
+    let sz = (self.sz_in + self.sz_out) as f64;
+    let max = opt.subp_sz_max.unwrap_or(2 * opt.subp_sz) as f64;
+    let f = (1.0 + opt.subp_growth * iters_wo_impr as f64).min(max / sz);
+    if f <= 1.0 {
+      return self;
+    }
+    Relaxation {
+      sz_in: (self.sz_in as f64 * f).round() as usize,
+      sz_out: (self.sz_out as f64 * f).round() as usize,
+      ..self
+    }
+  }
+}
+
+/// Subproblem sizes (in, out) given by --subp-sz, --subp-sz-in and
+/// --subp-sz-out
+pub fn base_sizes(opt: &Options) -> (usize, usize) {
+  let sz_in = opt.subp_sz_in.unwrap_or(opt.subp_sz / 2);
+  let sz_out =
+    opt.subp_sz_out.unwrap_or_else(|| opt.subp_sz.saturating_sub(sz_in));
+  (sz_in, sz_out)
+}
+
+/// Returns the relaxed vertices (in, out) of 's', out of the tabu vertices
+pub fn relax(
+  inst: &Instance,
+  s: &Solution,
+  rlx: &Relaxation,
+  alpha: f64,
+  tabu: &TabuList,
+) -> (Vec<usize>, Vec<usize>) {
+  match rlx.op {
+    RelaxOp::Greedy => {
+      // insertion first, to draw random numbers as before ALNS did
+      let vout =
+        greedy::insertion_order(inst, s, rlx.sz_out, alpha, Some(tabu));
+      let vin = greedy::removal_order(inst, s, rlx.sz_in, alpha, Some(tabu));
+      (vin, vout)
+    }
+    RelaxOp::Random => {
+      let (mut vin, mut vout) = free_vertices(inst, s, tabu);
+      fastrand::shuffle(&mut vin);
+      fastrand::shuffle(&mut vout);
+      vin.truncate(rlx.sz_in);
+      vout.truncate(rlx.sz_out);
+      (vin, vout)
+    }
+    RelaxOp::Related => {
+      // the vertices with the largest distances to a random seed vertex
+      let (mut vin, mut vout) = free_vertices(inst, s, tabu);
+      let seed = match random_seed(&vin, &vout) {
+        Some(u) => u,
+        None => return (vin, vout),
+      };
+      for (v, sz) in [(&mut vin, rlx.sz_in), (&mut vout, rlx.sz_out)] {
+        v.sort_unstable_by(|a, b| {
+          let (da, db) = (rank(inst, seed, *a), rank(inst, seed, *b));
+          db.partial_cmp(&da).unwrap()
+        });
+        v.truncate(sz);
+      }
+      (vin, vout)
+    }
+    RelaxOp::Cluster => cluster(inst, s, rlx, tabu),
+  }
+}
+
+/// Relatedness of 'i' to 'seed'; the seed is the most related to itself
+fn rank(inst: &Instance, seed: usize, i: usize) -> f64 {
+  if i == seed {
+    f64::MAX
+  } else {
+    inst.dist(seed, i)
+  }
+}
+
+/// Returns the non-tabu vertices (in, out) of 's'
+fn free_vertices(
+  inst: &Instance,
+  s: &Solution,
+  tabu: &TabuList,
+) -> (Vec<usize>, Vec<usize>) {
+  (0..inst.n).filter(|i| !tabu.is_tabu(*i)).partition(|i| s.has[*i])
+}
+
+/// Returns a random vertex of 'vin', or of 'vout' if 'vin' is empty
+fn random_seed(vin: &[usize], vout: &[usize]) -> Option<usize> {
+  let v = if vin.is_empty() {
+    vout
+  } else {
+    vin
+  };
+  if v.is_empty() {
+    None
+  } else {
+    Some(v[fastrand::usize(0..v.len())])
+  }
+}
+
+/// Grows a cluster from a random seed vertex of 's', adding the vertex with
+/// the largest sum of distances to the cluster while there is room on its side
+fn cluster(
+  inst: &Instance,
+  s: &Solution,
+  rlx: &Relaxation,
+  tabu: &TabuList,
+) -> (Vec<usize>, Vec<usize>) {
+  let (free_in, free_out) = free_vertices(inst, s, tabu);
+  let (mut vin, mut vout) = (Vec::new(), Vec::new());
+  let mut taken: Vec<bool> = (0..inst.n).map(|i| tabu.is_tabu(i)).collect();
+  let mut attraction = vec![0.0; inst.n];
+  let mut next = random_seed(&free_in, &free_out);
+  while let Some(u) = next {
+    taken[u] = true;
+    if s.has[u] {
+      vin.push(u);
+    } else {
+      vout.push(u);
+    }
+    for (i, a) in attraction.iter_mut().enumerate() {
+      *a += inst.dist(u, i);
+    }
+    next = None;
+    for i in 0..inst.n {
+      let room = if s.has[i] {
+        vin.len() < rlx.sz_in
+      } else {
+        vout.len() < rlx.sz_out
+      };
+      if !taken[i] && room && next.is_none_or(|j| attraction[i] > attraction[j])
+      {
+        next = Some(i);
+      }
+    }
+  }
+  (vin, vout)
+}
+
+/// Usage statistics of a choice
+#[derive(Clone, Default)]
+struct Stats {
+  used: usize,
+  improved: usize,
+  new_inc: usize,
+}
+
+/// Roulette wheel over a set of choices
+struct Wheel {
+  weights: Vec<f64>,
+  stats: Vec<Stats>,
+  last: usize,
+}
+
+impl Wheel {
+  fn new(sz: usize) -> Wheel {
+    Wheel {
+      weights: vec![1.0; sz],
+      stats: vec![Stats::default(); sz],
+      last: 0,
+    }
+  }
+
+  fn spin(&mut self) -> usize {
+    self.last = 0;
+    if self.weights.len() > 1 {
+      let mut r = fastrand::f64() * self.weights.iter().sum::<f64>();
+      while self.last + 1 < self.weights.len() && r >= self.weights[self.last] {
+        r -= self.weights[self.last];
+        self.last += 1;
+      }
+    }
+    self.stats[self.last].used += 1;
+    self.last
+  }
+
+  fn update(&mut self, score: f64, reaction: f64) {
+    let w = &mut self.weights[self.last];
+    *w = ((1.0 - reaction) * *w + reaction * score).max(MIN_WEIGHT);
+    let st = &mut self.stats[self.last];
+    st.improved += (score >= SCORE_IMPROVED) as usize;
+    st.new_inc += (score >= SCORE_INC) as usize;
+  }
+}
+
+/// Adaptive neighborhood selection
+pub struct Alns {
+  ops: Vec<RelaxOp>,
+  sizes: Vec<(usize, usize)>, // (sz_in, sz_out)
+  op_wheel: Wheel,
+  size_wheel: Wheel,
+  reaction: f64,
+}
+
+impl Alns {
+  /// Without --alns, always returns the greedy relaxation of the base
+  /// subproblem sizes
+  pub fn new(opt: &Options) -> Alns {
+    let (ops, sizes) = if opt.alns {
+      let (sz_in, sz_out) = base_sizes(opt);
+      let mut sizes = Vec::new();
+      for m in &opt.alns_sizes {
+        for r in &opt.alns_ratios {
+          let sz = (((sz_in + sz_out) as f64 * m).round() as usize).max(1);
+          let sz_in = (sz as f64 * r).round() as usize;
+          sizes.push((sz_in, sz - sz_in));
+        }
+      }
+      (opt.alns_ops.clone(), sizes)
+    } else {
+      (vec![RelaxOp::Greedy], vec![base_sizes(opt)])
+    };
+    Alns {
+      op_wheel: Wheel::new(ops.len()),
+      size_wheel: Wheel::new(sizes.len()),
+      ops,
+      sizes,
+      reaction: opt.alns_reaction,
+    }
+  }
+
+  /// Chooses the next neighborhood
+  pub fn choose(&mut self) -> Relaxation {
+    let op = self.ops[self.op_wheel.spin()];
+    let (sz_in, sz_out) = self.sizes[self.size_wheel.spin()];
+    Relaxation {
+      op,
+      sz_in,
+      sz_out,
+    }
+  }
+
+  /// Rewards the last neighborhood, given whether it improved the current
+  /// solution and the incumbent
+  pub fn update(&mut self, improved: bool, improved_inc: bool) {
+    let score = if improved_inc {
+      SCORE_INC
+    } else if improved {
+      SCORE_IMPROVED
+    } else {
+      0.0
+    };
+    self.op_wheel.update(score, self.reaction);
+    self.size_wheel.update(score, self.reaction);
+  }
+
+  /// Prints the statistics of each operator and size
+  pub fn report(&self) {
+    println!("alns op       weight   used  improved  new_inc");
+    for (op, (w, st)) in self
+      .ops
+      .iter()
+      .zip(self.op_wheel.weights.iter().zip(&self.op_wheel.stats))
+    {
+      println!(
+        "alns {:<8} {:>7.3} {:>6} {:>9} {:>8}",
+        format!("{:?}", op).to_lowercase(),
+        w,
+        st.used,
+        st.improved,
+        st.new_inc
+      );
+    }
+    println!("alns in/out    weight   used  improved  new_inc");
+    for ((sz_in, sz_out), (w, st)) in self
+      .sizes
+      .iter()
+      .zip(self.size_wheel.weights.iter().zip(&self.size_wheel.stats))
+    {
+      println!(
+        "alns {:>4}/{:<4} {:>7.3} {:>6} {:>9} {:>8}",
+        sz_in, sz_out, w, st.used, st.improved, st.new_inc
+      );
+    }
+  }
+}
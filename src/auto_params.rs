@@ -0,0 +1,217 @@
+// Instance-size-aware parameters (--auto-params): options chosen by the
+// features of the instance, from a table of instances, their features and the
+// best options on them, fitted from bench results (fit-auto subcommand). The
+// options are those voted by the K instances nearest in standardized
+// features, unless even the nearest one is too far for its options to apply,
+// and then the defaults are kept. No table is shipped: it is fitted by
+// fit-auto from bench runs on the instances at hand.
+
+use crate::bench::{self, glob, read_results};
+use crate::instance::Instance;
+use crate::options::{FitAutoOptions, Options};
+use std::path::Path;
+
+/// Number of nearest instances that vote on the options
+const K: usize = 3;
+
+/// Smallest scales of the features in distances, so that a table of similar
+/// instances doesn't set all others far apart: a factor 2 in size, 0.1 in
+/// density, 1 in mean and deviation and 0.5 in skewness
+const MIN_SCALE: [f64; 5] = [std::f64::consts::LN_2, 0.1, 1.0, 1.0, 0.5];
+
+/// Largest distance to the nearest instance of the table for its options to
+/// be used, in squared scales of the features
+const MAX_DIST: f64 = 1.0;
+
+/// Features of an instance, over the distances of its pairs of vertices
+#[derive(Clone, Debug)]
+pub struct Features {
+  pub n: usize,
+  pub density: f64, // share of positive distances
+  pub mean: f64,
+  pub std: f64,
+  pub skew: f64,
+}
+
+impl Features {
+  pub fn of(inst: &Instance) -> Features {
+    let pairs = (inst.n * (inst.n - 1) / 2) as f64;
+    let (mut pos, mut sum, mut sum2) = (0.0, 0.0, 0.0);
+    for i in 0..inst.n {
+      for j in (i + 1)..inst.n {
+        let d = inst.dist(i, j);
+        pos += (d > 0.0) as usize as f64;
+        sum += d;
+        sum2 += d * d;
+      }
+    }
+    let mean = sum / pairs;
+    let std = (sum2 / pairs - mean * mean).max(0.0).sqrt();
+    let mut sum3 = 0.0;
+    for i in 0..inst.n {
+      for j in (i + 1)..inst.n {
+        sum3 += (inst.dist(i, j) - mean).powi(3);
+      }
+    }
+    Features {
+      n: inst.n,
+      density: pos / pairs,
+      mean,
+      std,
+      skew: if std > 0.0 {
+        sum3 / pairs / std.powi(3)
+      } else {
+        0.0
+      },
+    }
+  }
+
+  /// Values compared between instances; the size in log scale
+  fn values(&self) -> [f64; 5] {
+    [(self.n as f64).ln(), self.density, self.mean, self.std, self.skew]
+  }
+}
+
+/// A row of the table: an instance, its features and its best options
+struct Row {
+  instance: String,
+  features: Features,
+  args: String,
+}
+
+/// Reads a table: lines "instance n density mean std skew | options", where
+/// lines starting with '#' are comments
+fn parse_table(text: &str) -> Vec<Row> {
+  let mut rows = Vec::new();
+  for l in text.lines().filter(|l| !l.trim_start().starts_with('#')) {
+    let (f, args) = match l.split_once('|') {
+      Some(x) => x,
+      None => continue,
+    };
+    let f: Vec<&str> = f.split_whitespace().collect();
+    let msg = &format!("Invalid line in auto-params table: \"{}\"", l);
+    if f.len() != 6 {
+      panic!("{}", msg);
+    }
+    let num = |i: usize| -> f64 { f[i].parse().expect(msg) };
+    rows.push(Row {
+      instance: f[0].to_string(),
+      features: Features {
+        n: f[1].parse().expect(msg),
+        density: num(2),
+        mean: num(3),
+        std: num(4),
+        skew: num(5),
+      },
+      args: args.trim().to_string(),
+    });
+  }
+  rows
+}
+
+fn format_row(r: &Row) -> String {
+  let f = &r.features;
+  format!(
+    "{:>12} {:>5} {:>7.4} {:>8.4} {:>8.4} {:>8.4} | {}",
+    r.instance, f.n, f.density, f.mean, f.std, f.skew, r.args
+  )
+}
+
+/// Options for 'inst' by the table of --auto-table: the most voted among
+/// the K nearest instances, ties broken by the nearest one; none if the
+/// nearest one is farther than MAX_DIST
+pub fn choose(inst: &Instance, opt: &Options) -> (Features, Vec<String>) {
+  let file = opt.auto_table.as_ref().expect("No --auto-table");
+  let text = std::fs::read_to_string(file).expect("Unable to open table");
+  let rows = parse_table(&text);
+  let features = Features::of(inst);
+  if rows.is_empty() {
+    return (features, Vec::new());
+  }
+
+  // distances in features standardized over the table
+  let values: Vec<[f64; 5]> =
+    rows.iter().map(|r| r.features.values()).collect();
+  let mut scale = [0.0; 5];
+  for (k, s) in scale.iter_mut().enumerate() {
+    let x: Vec<f64> = values.iter().map(|v| v[k]).collect();
+    let m = x.iter().sum::<f64>() / x.len() as f64;
+    let var = x.iter().map(|v| (v - m).powi(2)).sum::<f64>() / x.len() as f64;
+    *s = var.sqrt().max(MIN_SCALE[k]);
+  }
+  let target = features.values();
+  let dist = |v: &[f64; 5]| -> f64 {
+    (0..5).map(|k| ((v[k] - target[k]) / scale[k]).powi(2)).sum()
+  };
+  let mut order: Vec<usize> = (0..rows.len()).collect();
+  order
+    .sort_by(|&a, &b| dist(&values[a]).partial_cmp(&dist(&values[b])).unwrap());
+  if dist(&values[order[0]]) > MAX_DIST {
+    return (features, Vec::new());
+  }
+  let nearest = &order[..K.min(order.len())];
+  let votes =
+    |args: &str| nearest.iter().filter(|&&i| rows[i].args == args).count();
+  let most = nearest.iter().map(|&i| votes(&rows[i].args)).max().unwrap();
+  let best = nearest.iter().find(|&&i| votes(&rows[i].args) == most).unwrap();
+  let args = rows[*best].args.split_whitespace().map(|a| a.to_string());
+  (features, args.collect())
+}
+
+/// Runs the fit-auto subcommand: for each instance, the configuration of the
+/// bench results with the best average value becomes its row of the table
+pub fn fit(f: &FitAutoOptions) {
+  let configs: Vec<bench::Config> =
+    f.configs.iter().map(|c| bench::parse_config(c)).collect();
+  let results: Vec<Vec<bench::Row>> = configs
+    .iter()
+    .map(|c| {
+      let file = format!("{}.{}", c.name, bench::extension(f.format));
+      read_results(&Path::new(&f.out_dir).join(file))
+    })
+    .collect();
+
+  let mut rows = Vec::new();
+  for path in glob(&f.instances) {
+    let name = path.file_stem().unwrap().to_string_lossy().to_string();
+    let avg = |rs: &[bench::Row]| -> Option<f64> {
+      let v: Vec<f64> =
+        rs.iter().filter(|r| r.instance == name).map(|r| r.value).collect();
+      (!v.is_empty()).then(|| v.iter().sum::<f64>() / v.len() as f64)
+    };
+    let mut best: Option<(usize, f64)> = None;
+    for (k, rs) in results.iter().enumerate() {
+      if let Some(v) = avg(rs) {
+        if best.is_none_or(|(_, b)| gr!(v, b)) {
+          best = Some((k, v));
+        }
+      }
+    }
+    let k = match best {
+      Some((k, _)) => k,
+      None => {
+        eprintln!("No results for {}; skipped", name);
+        continue;
+      }
+    };
+    let inst = Instance::read_from_file(path.to_str().unwrap());
+    let row = Row {
+      instance: name,
+      features: Features::of(&inst),
+      args: configs[k].args.join(" "),
+    };
+    println!("{}  ({})", format_row(&row), configs[k].name);
+    rows.push(row);
+  }
+
+  let mut out = String::from(
+    "# Options of --auto-params, written by the fit-auto subcommand: the best\n\
+     # configuration of each instance, with its features; instances unlike\n\
+     # all of these keep the default options\n\
+     # instance n density mean std skew | options\n",
+  );
+  for r in &rows {
+    out += &(format_row(r) + "\n");
+  }
+  std::fs::write(&f.table, out).expect("Unable to write table");
+}
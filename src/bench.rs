@@ -0,0 +1,380 @@
+// Benchmark runner (bench subcommand): runs this program on every combination
+// of configuration, instance and seed in a pool of workers, each run in its
+// own process, and collects their summary lines into results tables. Results
+// are appended as runs finish, so an interrupted experiment resumes where it
+// stopped.
+
+use crate::options::{BenchOptions, Format};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// A row of a results table
+#[derive(Clone, Debug)]
+pub struct Row {
+  pub instance: String,
+  pub value: f64,
+  pub size: usize,
+  pub ttb: f64,
+  pub seed: u64,
+}
+
+/// A named configuration: command-line options added to each run
+pub struct Config {
+  pub name: String,
+  pub args: Vec<String>,
+}
+
+/// A run to be done
+struct Job {
+  config: usize,
+  instance: PathBuf,
+  seed: u64,
+}
+
+/// Whether 'name' matches 'pattern', where '*' matches any sequence of
+/// characters and '?' any single character
+fn wildcard_match(pattern: &[u8], name: &[u8]) -> bool {
+  match (pattern.first(), name.first()) {
+    (None, None) => true,
+    (Some(b'*'), _) => {
+      wildcard_match(&pattern[1..], name)
+        || (!name.is_empty() && wildcard_match(pattern, &name[1..]))
+    }
+    (Some(b'?'), Some(_)) => wildcard_match(&pattern[1..], &name[1..]),
+    (Some(p), Some(c)) if p == c => wildcard_match(&pattern[1..], &name[1..]),
+    _ => false,
+  }
+}
+
+/// Files matching a glob pattern; wildcards are only allowed in the file name
+pub fn glob(pattern: &str) -> Vec<PathBuf> {
+  let path = Path::new(pattern);
+  let dir = match path.parent() {
+    Some(d) if !d.as_os_str().is_empty() => d,
+    _ => Path::new("."),
+  };
+  let file_pattern = path
+    .file_name()
+    .and_then(|f| f.to_str())
+    .expect("Invalid instance pattern");
+  let mut files: Vec<PathBuf> = fs::read_dir(dir)
+    .expect("Unable to read instance directory")
+    .filter_map(|e| e.ok())
+    .map(|e| e.path())
+    .filter(|p| {
+      p.is_file()
+        && p.file_name().and_then(|f| f.to_str()).is_some_and(|f| {
+          wildcard_match(file_pattern.as_bytes(), f.as_bytes())
+        })
+    })
+    .collect();
+  files.sort();
+  files
+}
+
+/// Parses seeds given as a range "first-last" or a list "s1,s2,..."
+pub fn parse_seeds(seeds: &str) -> Vec<u64> {
+  let msg = &format!("Invalid seeds \"{}\"", seeds);
+  match seeds.split_once('-') {
+    Some((a, b)) => {
+      (a.trim().parse().expect(msg)..=b.trim().parse().expect(msg)).collect()
+    }
+    None => seeds.split(',').map(|s| s.trim().parse().expect(msg)).collect(),
+  }
+}
+
+/// Parses a configuration "name=options"
+pub fn parse_config(config: &str) -> Config {
+  let (name, args) = config.split_once('=').unwrap_or((config, ""));
+  Config {
+    name: name.trim().to_string(),
+    args: args.split_whitespace().map(|s| s.to_string()).collect(),
+  }
+}
+
+/// Parses the summary line of a run's output
+pub fn parse_summary(output: &str) -> Option<Row> {
+  let line = output.lines().find(|l| l.starts_with("summary_line"))?;
+  let kv: HashMap<&str, &str> =
+    line.split_whitespace().filter_map(|t| t.split_once('=')).collect();
+  Some(Row {
+    instance: kv.get("instance")?.to_string(),
+    value: kv.get("value")?.parse().ok()?,
+    size: kv.get("size")?.parse().ok()?,
+    ttb: kv.get("ttb")?.parse().ok()?,
+    seed: kv.get("seed")?.parse().ok()?,
+  })
+}
+
+/// File extension of a results table
+pub fn extension(format: Format) -> &'static str {
+  match format {
+    Format::Table => "dat",
+    Format::Csv => "csv",
+    Format::Json => "jsonl",
+  }
+}
+
+fn header(format: Format) -> Option<String> {
+  match format {
+    Format::Table => Some(format!(
+      "{:>12} {:>11} {:>5} {:>10} {:>5}",
+      "instance", "value", "size", "ttb", "seed"
+    )),
+    Format::Csv => Some("instance,value,size,ttb,seed".to_string()),
+    Format::Json => None,
+  }
+}
+
+fn format_row(r: &Row, format: Format) -> String {
+  match format {
+    Format::Table => format!(
+      "{:>12} {:>11.6} {:>5} {:>10.6} {:>5}",
+      r.instance, r.value, r.size, r.ttb, r.seed
+    ),
+    Format::Csv => format!(
+      "{},{:.6},{},{:.6},{}",
+      r.instance, r.value, r.size, r.ttb, r.seed
+    ),
+    Format::Json => format!(
+      "{{\"instance\":\"{}\",\"value\":{:.6},\"size\":{},\"ttb\":{:.6},\
+       \"seed\":{}}}",
+      r.instance, r.value, r.size, r.ttb, r.seed
+    ),
+  }
+}
+
+/// Parses a row of a results table; None for headers and invalid lines
+fn parse_row(line: &str, format: Format) -> Option<Row> {
+  let fields: Vec<&str> = match format {
+    Format::Table => line.split_whitespace().collect(),
+    Format::Csv => line.split(',').map(|f| f.trim()).collect(),
+    Format::Json => {
+      let kv: HashMap<&str, &str> = line
+        .trim()
+        .trim_start_matches('{')
+        .trim_end_matches('}')
+        .split(',')
+        .filter_map(|f| f.split_once(':'))
+        .map(|(k, v)| (k.trim().trim_matches('"'), v.trim().trim_matches('"')))
+        .collect();
+      ["instance", "value", "size", "ttb", "seed"]
+        .iter()
+        .map(|k| kv.get(k).copied())
+        .collect::<Option<_>>()?
+    }
+  };
+  if fields.len() != 5 {
+    return None;
+  }
+  Some(Row {
+    instance: fields[0].to_string(),
+    value: fields[1].parse().ok()?,
+    size: fields[2].parse().ok()?,
+    ttb: fields[3].parse().ok()?,
+    seed: fields[4].parse().ok()?,
+  })
+}
+
+/// Reads a results table; the format is given by the file extension
+pub fn read_results(path: &Path) -> Vec<Row> {
+  let format = match path.extension().and_then(|e| e.to_str()) {
+    Some("csv") => Format::Csv,
+    Some("jsonl") | Some("json") => Format::Json,
+    _ => Format::Table,
+  };
+  match fs::read_to_string(path) {
+    Ok(data) => data.lines().filter_map(|l| parse_row(l, format)).collect(),
+    Err(_) => Vec::new(),
+  }
+}
+
+/// Writes a results table, sorted by instance and seed
+fn write_results(path: &Path, rows: &mut [Row], format: Format) {
+  rows.sort_by(|a, b| (&a.instance, a.seed).cmp(&(&b.instance, b.seed)));
+  let mut f = File::create(path).expect("Unable to write results");
+  if let Some(h) = header(format) {
+    writeln!(f, "{}", h).expect("Unable to write results");
+  }
+  for r in rows.iter() {
+    writeln!(f, "{}", format_row(r, format)).expect("Unable to write results");
+  }
+}
+
+/// Runs this program on 'job', in a process with the memory limit of 'b',
+/// killed if it runs past the time limit plus a grace period (half the time
+/// limit plus 30 seconds). Returns the row of its summary line.
+fn run_job(
+  exe: &Path,
+  b: &BenchOptions,
+  config: &Config,
+  job: &Job,
+) -> Result<Row, String> {
+  let mut child = Command::new("sh")
+    .arg("-c")
+    .arg(format!("ulimit -v {} && exec \"$0\" \"$@\"", b.mem_limit * 1024))
+    .arg(exe)
+    .arg("-i")
+    .arg(&job.instance)
+    .args(["-t", &b.time_limit.to_string()])
+    .args(["-s", &job.seed.to_string()])
+    .args(&config.args)
+    .stdin(Stdio::null())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .spawn()
+    .map_err(|e| format!("unable to start: {}", e))?;
+
+  // read the output concurrently, so that the child never blocks on a pipe
+  let mut stdout = child.stdout.take().unwrap();
+  let mut stderr = child.stderr.take().unwrap();
+  let out = std::thread::spawn(move || {
+    let mut s = String::new();
+    stdout.read_to_string(&mut s).ok();
+    s
+  });
+  let err = std::thread::spawn(move || {
+    let mut s = String::new();
+    stderr.read_to_string(&mut s).ok();
+    s
+  });
+
+  let deadline =
+    Instant::now() + Duration::from_secs(b.time_limit + b.time_limit / 2 + 30);
+  let status = loop {
+    match child.try_wait().map_err(|e| e.to_string())? {
+      Some(status) => break status,
+      None if Instant::now() > deadline => {
+        child.kill().ok();
+        child.wait().ok();
+        return Err("killed after the time limit".to_string());
+      }
+      None => std::thread::sleep(Duration::from_millis(100)),
+    }
+  };
+  let out = out.join().unwrap_or_default();
+  let err = err.join().unwrap_or_default();
+  if !status.success() {
+    return Err(format!("{}: {}", status, err.trim()));
+  }
+  parse_summary(&out).ok_or_else(|| "no summary line".to_string())
+}
+
+/// Runs the bench subcommand
+pub fn run(b: &BenchOptions) {
+  let exe = std::env::current_exe().expect("Unable to find own executable");
+  let configs: Vec<Config> = if b.configs.is_empty() {
+    vec![parse_config("default")]
+  } else {
+    b.configs.iter().map(|c| parse_config(c)).collect()
+  };
+  let instances = glob(&b.instances);
+  let seeds = parse_seeds(&b.seeds);
+  fs::create_dir_all(&b.out_dir).expect("Unable to create output directory");
+
+  // skip the runs already in the results tables
+  let paths: Vec<PathBuf> = configs
+    .iter()
+    .map(|c| {
+      Path::new(&b.out_dir).join(format!("{}.{}", c.name, extension(b.format)))
+    })
+    .collect();
+  let mut jobs: Vec<Job> = Vec::new();
+  for (k, path) in paths.iter().enumerate() {
+    let done = read_results(path);
+    for instance in &instances {
+      let name = instance.file_stem().unwrap().to_string_lossy();
+      for seed in &seeds {
+        if !done.iter().any(|r| r.instance == name && r.seed == *seed) {
+          jobs.push(Job {
+            config: k,
+            instance: instance.clone(),
+            seed: *seed,
+          });
+        }
+      }
+    }
+  }
+  let total = configs.len() * instances.len() * seeds.len();
+  println!(
+    "{} runs: {} configurations, {} instances, {} seeds; {} already done",
+    total,
+    configs.len(),
+    instances.len(),
+    seeds.len(),
+    total - jobs.len()
+  );
+
+  let writers: Vec<Mutex<File>> = paths
+    .iter()
+    .map(|p| {
+      let new = !fs::metadata(p).is_ok_and(|m| m.len() > 0);
+      let mut f = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(p)
+        .expect("Unable to open results");
+      if new {
+        if let Some(h) = header(b.format) {
+          writeln!(f, "{}", h).expect("Unable to write results");
+        }
+      }
+      Mutex::new(f)
+    })
+    .collect();
+
+  let nb_jobs = jobs.len();
+  jobs.reverse(); // jobs are popped from the back
+  let queue = Mutex::new(jobs);
+  let finished = Mutex::new(0);
+  std::thread::scope(|scope| {
+    for _ in 0..b.jobs.max(1) {
+      scope.spawn(|| loop {
+        let job = match queue.lock().unwrap().pop() {
+          Some(job) => job,
+          None => break,
+        };
+        let config = &configs[job.config];
+        let res = run_job(&exe, b, config, &job);
+        let mut finished = finished.lock().unwrap();
+        *finished += 1;
+        match res {
+          Ok(row) => {
+            let mut f = writers[job.config].lock().unwrap();
+            writeln!(f, "{}", format_row(&row, b.format))
+              .and_then(|_| f.flush())
+              .expect("Unable to write results");
+            println!(
+              "[{}/{}] {} {} seed {}: value {:.4} ttb {:.2}",
+              *finished,
+              nb_jobs,
+              config.name,
+              row.instance,
+              row.seed,
+              row.value,
+              row.ttb
+            );
+          }
+          Err(e) => eprintln!(
+            "[{}/{}] {} {} seed {} failed: {}",
+            *finished,
+            nb_jobs,
+            config.name,
+            job.instance.display(),
+            job.seed,
+            e
+          ),
+        }
+      });
+    }
+  });
+
+  for path in &paths {
+    write_results(path, &mut read_results(path), b.format);
+  }
+}
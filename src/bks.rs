@@ -0,0 +1,96 @@
+// Registry of best-known solutions (--bks-file): a text file with a line
+// "instance value size source [solution]" per instance, where the solution is
+// an optional file in the format of --output. Lines starting with '#' are
+// comments.
+
+use std::fs;
+
+/// Best-known solution of an instance
+#[derive(Clone, Debug)]
+pub struct Entry {
+  pub instance: String,
+  pub value: f64,
+  pub size: usize,
+  pub source: String, // who found it, e.g. a paper or a run
+  pub solution: Option<String>, // file with its vertices
+}
+
+fn parse_entry(line: &str) -> Option<Entry> {
+  if line.trim_start().starts_with('#') {
+    return None;
+  }
+  let f: Vec<&str> = line.split_whitespace().collect();
+  if f.len() < 4 {
+    return None;
+  }
+  Some(Entry {
+    instance: f[0].to_string(),
+    value: f[1].parse().ok()?,
+    size: f[2].parse().ok()?,
+    source: f[3].to_string(),
+    solution: f.get(4).map(|s| s.to_string()),
+  })
+}
+
+fn format_entry(e: &Entry) -> String {
+  format!(
+    "{:>12} {:>11.6} {:>5} {}{}",
+    e.instance,
+    e.value,
+    e.size,
+    e.source,
+    e.solution.as_ref().map_or(String::new(), |s| format!(" {}", s))
+  )
+}
+
+/// Reads the entries of a registry; none if it doesn't exist
+pub fn read(path: &str) -> Vec<Entry> {
+  match fs::read_to_string(path) {
+    Ok(data) => data.lines().filter_map(parse_entry).collect(),
+    Err(_) => Vec::new(),
+  }
+}
+
+/// The entry of 'instance' in a registry
+pub fn lookup(path: &str, instance: &str) -> Option<Entry> {
+  read(path).into_iter().find(|e| e.instance == instance)
+}
+
+/// Stores 'entry' in a registry, unless its instance already has an equal or
+/// better value there. The registry is read again just before, and replaced
+/// at once, so that concurrent runs (e.g. of bench) lose no entries but the
+/// rare ones written in between. Returns whether it was stored.
+pub fn update(path: &str, entry: Entry) -> bool {
+  let data = fs::read_to_string(path).unwrap_or_default();
+  let mut lines: Vec<String> = Vec::new();
+  let mut found = false;
+  for l in data.lines() {
+    match parse_entry(l) {
+      Some(e) if e.instance == entry.instance => {
+        if !gr!(entry.value, e.value) {
+          return false;
+        }
+        lines.push(format_entry(&entry));
+        found = true;
+      }
+      _ => lines.push(l.to_string()),
+    }
+  }
+  if !found {
+    lines.push(format_entry(&entry));
+  }
+  let tmp = format!("{}.tmp{}", path, std::process::id());
+  fs::write(&tmp, lines.join("\n") + "\n")
+    .and_then(|_| fs::rename(&tmp, path))
+    .expect("Unable to write BKS file");
+  true
+}
+
+/// Relative percentage deviation of 'v' from 'bks', for maximization
+pub fn rpd(v: f64, bks: f64) -> f64 {
+  if bks == 0.0 {
+    0.0
+  } else {
+    100.0 * (bks - v) / bks.abs()
+  }
+}
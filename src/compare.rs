@@ -0,0 +1,279 @@
+// Analysis of results tables (compare subcommand): per-instance statistics of
+// each algorithm, deviations from the best-known values, and pairwise and
+// overall tests between the algorithms.
+
+use crate::bench::{read_results, Row};
+use crate::bks::rpd;
+use crate::options::{CompareOptions, TableStyle};
+use crate::stats;
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+
+/// Statistics of the runs of an algorithm on an instance
+struct Summary {
+  best: f64,
+  avg: f64,
+  std: f64,
+  worst: f64,
+  ttb: f64,
+}
+
+impl Summary {
+  fn new(rows: &[&Row]) -> Summary {
+    let values: Vec<f64> = rows.iter().map(|r| r.value).collect();
+    let ttbs: Vec<f64> = rows.iter().map(|r| r.ttb).collect();
+    Summary {
+      best: values.iter().cloned().fold(f64::MIN, f64::max),
+      avg: stats::mean(&values),
+      std: stats::std_dev(&values),
+      worst: values.iter().cloned().fold(f64::MAX, f64::min),
+      ttb: stats::mean(&ttbs),
+    }
+  }
+}
+
+/// Results of an algorithm, by instance
+struct Algorithm {
+  name: String,
+  by_instance: HashMap<String, Summary>,
+}
+
+impl Algorithm {
+  /// Reads a results table given as "name=file", or "file" to name it after
+  /// the file name
+  fn read(table: &str) -> Algorithm {
+    let (name, file) = match table.split_once('=') {
+      Some((name, file)) => (name.to_string(), file),
+      None => (
+        Path::new(table).file_stem().unwrap().to_string_lossy().to_string(),
+        table,
+      ),
+    };
+    let rows = read_results(Path::new(file));
+    if rows.is_empty() {
+      panic!("No results in \"{}\"", file);
+    }
+    let mut grouped: HashMap<&str, Vec<&Row>> = HashMap::new();
+    for r in &rows {
+      grouped.entry(&r.instance).or_default().push(r);
+    }
+    Algorithm {
+      name,
+      by_instance: grouped
+        .into_iter()
+        .map(|(i, rs)| (i.to_string(), Summary::new(&rs)))
+        .collect(),
+    }
+  }
+}
+
+/// Reads best-known values from a file with an instance and its value in the
+/// first two columns of each line, such as a results table; other lines are
+/// ignored. The best value of each instance is kept.
+pub fn read_bks(path: &str) -> HashMap<String, f64> {
+  let data = std::fs::read_to_string(path).expect("Unable to open BKS file");
+  let mut bks: HashMap<String, f64> = HashMap::new();
+  for l in data.lines() {
+    let mut fields = l.split_whitespace();
+    if let (Some(i), Some(Ok(v))) =
+      (fields.next(), fields.next().map(|v| v.parse::<f64>()))
+    {
+      let b = bks.entry(i.to_string()).or_insert(v);
+      *b = b.max(v);
+    }
+  }
+  bks
+}
+
+/// Formats a p-value, in scientific notation if small
+fn format_p(p: f64) -> String {
+  if p < 1e-4 {
+    format!("{:.2e}", p)
+  } else {
+    format!("{:.4}", p)
+  }
+}
+
+/// Prints a line of text, as a comment in LaTeX
+fn note(text: &str, style: TableStyle) {
+  match style {
+    TableStyle::Markdown => println!("{}\n", text),
+    TableStyle::Latex => println!("% {}\n", text),
+  }
+}
+
+/// A table printed in Markdown or LaTeX
+struct Table {
+  header: Vec<String>,
+  rows: Vec<Vec<String>>,
+}
+
+impl Table {
+  fn new(header: Vec<String>) -> Table {
+    Table {
+      header,
+      rows: Vec::new(),
+    }
+  }
+
+  fn print(&self, title: &str, style: TableStyle) {
+    match style {
+      TableStyle::Markdown => {
+        println!("## {}\n", title);
+        println!("| {} |", self.header.join(" | "));
+        println!("|{}", "---|".repeat(self.header.len()));
+        for r in &self.rows {
+          println!("| {} |", r.join(" | "));
+        }
+      }
+      TableStyle::Latex => {
+        let esc = |s: &String| s.replace('_', "\\_");
+        println!("% {}", title);
+        println!(
+          "\\begin{{tabular}}{{l{}}}",
+          "r".repeat(self.header.len() - 1)
+        );
+        println!("\\hline");
+        let h: Vec<String> = self.header.iter().map(esc).collect();
+        println!("{} \\\\", h.join(" & "));
+        println!("\\hline");
+        for r in &self.rows {
+          let r: Vec<String> = r.iter().map(esc).collect();
+          println!("{} \\\\", r.join(" & "));
+        }
+        println!("\\hline");
+        println!("\\end{{tabular}}");
+      }
+    }
+    println!();
+  }
+}
+
+/// Runs the compare subcommand
+pub fn run(c: &CompareOptions) {
+  let algs: Vec<Algorithm> =
+    c.tables.iter().map(|t| Algorithm::read(t)).collect();
+
+  // compare on the instances solved by all algorithms
+  let all: BTreeSet<&String> =
+    algs.iter().flat_map(|a| a.by_instance.keys()).collect();
+  let instances: Vec<&String> = all
+    .iter()
+    .filter(|i| algs.iter().all(|a| a.by_instance.contains_key(**i)))
+    .cloned()
+    .collect();
+  if instances.len() < all.len() {
+    let text = format!(
+      "Comparing {} instances solved by all algorithms; {} skipped",
+      instances.len(),
+      all.len() - instances.len()
+    );
+    note(&text, c.style);
+  }
+
+  // best-known values: the best of the file and of all algorithms
+  let mut bks = c.bks.as_deref().map(read_bks).unwrap_or_default();
+  for i in &instances {
+    let found = algs.iter().map(|a| a.by_instance[*i].best);
+    let b = found.fold(bks.get(*i).copied().unwrap_or(f64::MIN), f64::max);
+    bks.insert(i.to_string(), b);
+  }
+
+  let mut per_instance = Table::new(vec!["instance".into(), "bks".into()]);
+  for a in &algs {
+    for col in ["best", "avg", "std", "worst", "ttb"] {
+      per_instance.header.push(format!("{} {}", a.name, col));
+    }
+  }
+  for i in &instances {
+    let mut row = vec![i.to_string(), format!("{:.4}", bks[*i])];
+    for a in &algs {
+      let s = &a.by_instance[*i];
+      row.push(format!("{:.4}", s.best));
+      row.push(format!("{:.4}", s.avg));
+      row.push(format!("{:.4}", s.std));
+      row.push(format!("{:.4}", s.worst));
+      row.push(format!("{:.2}", s.ttb));
+    }
+    per_instance.rows.push(row);
+  }
+  per_instance.print("Results per instance", c.style);
+
+  // tests are on the average value of each instance
+  let avgs: Vec<Vec<f64>> = algs
+    .iter()
+    .map(|a| instances.iter().map(|i| a.by_instance[*i].avg).collect())
+    .collect();
+  let blocks: Vec<Vec<f64>> =
+    (0..instances.len()).map(|k| avgs.iter().map(|v| v[k]).collect()).collect();
+  let (friedman, mean_ranks) = stats::friedman(&blocks);
+
+  let mut summary = Table::new(
+    [
+      "algorithm",
+      "RPD best (%)",
+      "RPD avg (%)",
+      "avg ttb",
+      "#bks",
+      "mean rank",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect(),
+  );
+  for (a, rank) in algs.iter().zip(&mean_ranks) {
+    let s: Vec<&Summary> =
+      instances.iter().map(|i| &a.by_instance[*i]).collect();
+    let rpd_of = |f: &dyn Fn(&Summary) -> f64| {
+      let v: Vec<f64> =
+        instances.iter().zip(&s).map(|(i, s)| rpd(f(s), bks[*i])).collect();
+      stats::mean(&v)
+    };
+    let ttbs: Vec<f64> = s.iter().map(|s| s.ttb).collect();
+    let hits =
+      instances.iter().zip(&s).filter(|(i, s)| !le!(s.best, bks[**i])).count();
+    summary.rows.push(vec![
+      a.name.clone(),
+      format!("{:.4}", rpd_of(&|s| s.best)),
+      format!("{:.4}", rpd_of(&|s| s.avg)),
+      format!("{:.2}", stats::mean(&ttbs)),
+      hits.to_string(),
+      format!("{:.2}", rank),
+    ]);
+  }
+  summary.print("Summary", c.style);
+
+  let mut pairwise = Table::new(
+    ["comparison", "wins", "ties", "losses", "W+", "p (Wilcoxon)"]
+      .iter()
+      .map(|s| s.to_string())
+      .collect(),
+  );
+  for x in 0..algs.len() {
+    for y in (x + 1)..algs.len() {
+      let (a, b) = (&avgs[x], &avgs[y]);
+      let wins = a.iter().zip(b).filter(|(u, v)| gr!(**u, **v)).count();
+      let ties = a.iter().zip(b).filter(|(u, v)| eq!(**u, **v)).count();
+      let test = stats::wilcoxon(a, b);
+      pairwise.rows.push(vec![
+        format!("{} vs {}", algs[x].name, algs[y].name),
+        wins.to_string(),
+        ties.to_string(),
+        (a.len() - wins - ties).to_string(),
+        format!("{:.1}", test.statistic),
+        format_p(test.p),
+      ]);
+    }
+  }
+  pairwise.print("Pairwise comparisons of average values", c.style);
+
+  if algs.len() > 1 {
+    let text = format!(
+      "Friedman test: chi-squared = {:.4}, df = {}, p = {}",
+      friedman.statistic,
+      algs.len() - 1,
+      format_p(friedman.p)
+    );
+    note(&text, c.style);
+  }
+}
@@ -0,0 +1,177 @@
+// Options from configuration files (--config) and named presets (--preset).
+// Both are flat lists of options by their long names, in TOML ("name = value"
+// lines) or in JSON (an object); they are turned into command-line options
+// placed before the actual ones, which thus override them. Flags they set are
+// turned off by "--no-<flag>" on the command line.
+
+use crate::options::Options;
+use clap::{ArgMatches, CommandFactory, FromArgMatches};
+
+/// Calibrated settings, by name; their files are in presets/
+const PRESETS: &[(&str, &str)] =
+  &[("brimberg-300s", include_str!("../presets/brimberg-300s.toml"))];
+
+/// Removes the quotes of a string value
+fn unquote(v: &str) -> &str {
+  v.trim().trim_matches('"').trim_matches('\'')
+}
+
+/// Splits 'text' on 'sep' outside of quotes and brackets
+fn split_top(text: &str, sep: char) -> Vec<&str> {
+  let mut parts = Vec::new();
+  let (mut depth, mut quoted, mut start) = (0, false, 0);
+  for (i, c) in text.char_indices() {
+    match c {
+      '"' => quoted = !quoted,
+      '[' | '{' if !quoted => depth += 1,
+      ']' | '}' if !quoted => depth -= 1,
+      c if c == sep && !quoted && depth == 0 => {
+        parts.push(&text[start..i]);
+        start = i + 1;
+      }
+      _ => {}
+    }
+  }
+  parts.push(&text[start..]);
+  parts
+}
+
+/// The (name, value) pairs of a configuration in TOML or JSON; arrays become
+/// comma-separated values
+fn parse_pairs(text: &str, json: bool) -> Vec<(String, String)> {
+  let entries: Vec<&str> = if json {
+    let body = text.trim().trim_start_matches('{').trim_end_matches('}');
+    split_top(body, ',')
+  } else {
+    text.lines().map(|l| split_top(l, '#')[0]).collect()
+  };
+  let sep = if json {
+    ':'
+  } else {
+    '='
+  };
+  let mut pairs = Vec::new();
+  for e in entries.iter().filter(|e| !e.trim().is_empty()) {
+    let (k, v) = e.split_once(sep).unwrap_or_else(|| {
+      panic!("Invalid configuration entry \"{}\"", e.trim())
+    });
+    let v = v.trim();
+    let v = if v.starts_with('[') {
+      let items = v.trim_start_matches('[').trim_end_matches(']');
+      let items: Vec<&str> = split_top(items, ',')
+        .into_iter()
+        .map(unquote)
+        .filter(|i| !i.is_empty())
+        .collect();
+      items.join(",")
+    } else {
+      unquote(v).to_string()
+    };
+    pairs.push((unquote(k).replace('_', "-"), v));
+  }
+  pairs
+}
+
+/// Command-line options of a configuration; flags are given as true or false
+fn config_args(text: &str, json: bool) -> Vec<String> {
+  let cmd = Options::command();
+  let mut args = Vec::new();
+  for (name, value) in parse_pairs(text, json) {
+    let arg =
+      cmd.get_arguments().find(|a| a.get_long() == Some(&name)).unwrap_or_else(
+        || panic!("Unknown option \"{}\" in configuration", name),
+      );
+    if arg.is_takes_value_set() {
+      args.push(format!("--{}", name));
+      args.push(value);
+    } else if value.parse().unwrap_or_else(|_| {
+      panic!("Option \"{}\" must be true or false, not \"{}\"", name, value)
+    }) {
+      args.push(format!("--{}", name));
+    }
+  }
+  args
+}
+
+/// The flag turned off by 'a', if it's "--no-<flag>" for a flag of Options
+fn negated_flag(a: &str) -> Option<String> {
+  let name = a.strip_prefix("--no-")?;
+  Options::command()
+    .get_arguments()
+    .any(|arg| arg.get_long() == Some(name) && !arg.is_takes_value_set())
+    .then(|| format!("--{}", name))
+}
+
+/// The value of option 'name' in the command line, given as "--name value" or
+/// "--name=value"
+fn find_arg(argv: &[String], name: &str) -> Option<String> {
+  let prefix = format!("--{}=", name);
+  argv.iter().enumerate().find_map(|(i, a)| {
+    if *a == format!("--{}", name) {
+      argv.get(i + 1).cloned()
+    } else {
+      a.strip_prefix(&prefix).map(|v| v.to_string())
+    }
+  })
+}
+
+/// Parses the options of the command line, after 'extra' options, then the
+/// ones of its --preset and then of its --config file, less the flags turned
+/// off by its "--no-<flag>" options. Returns them with their matches, for
+/// effective_options().
+pub fn parse(extra: &[String]) -> (Options, ArgMatches) {
+  let argv: Vec<String> = std::env::args().collect();
+  let mut full = vec![argv[0].clone()];
+  full.extend(extra.iter().cloned());
+
+  // options of the program, before the subcommand if any
+  let cmd = Options::command();
+  let own = argv
+    .iter()
+    .position(|a| cmd.get_subcommands().any(|s| s.get_name() == a))
+    .unwrap_or(argv.len());
+  let argv_own = &argv[..own];
+  if let Some(name) = find_arg(argv_own, "preset") {
+    let preset = PRESETS.iter().find(|p| p.0 == name).unwrap_or_else(|| {
+      let names: Vec<&str> = PRESETS.iter().map(|p| p.0).collect();
+      panic!("Unknown preset \"{}\"; presets: {}", name, names.join(", "))
+    });
+    full.extend(config_args(preset.1, false));
+  }
+  if let Some(file) = find_arg(argv_own, "config") {
+    let text =
+      std::fs::read_to_string(&file).expect("Unable to open configuration");
+    full.extend(config_args(&text, file.ends_with(".json")));
+  }
+  let negated: Vec<String> =
+    argv_own.iter().filter_map(|a| negated_flag(a)).collect();
+  full.retain(|a| !negated.contains(a));
+  full.extend(argv.into_iter().skip(1).filter(|a| negated_flag(a).is_none()));
+  let matches = Options::command().get_matches_from(full);
+  let opt = Options::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+  (opt, matches)
+}
+
+/// The effective options of a run, as command-line options, to reproduce it
+pub fn effective_options(matches: &ArgMatches) -> String {
+  let mut args = Vec::new();
+  for arg in Options::command().get_arguments() {
+    let (id, long) = match arg.get_long() {
+      Some(long)
+        if !["config", "preset", "help", "version"].contains(&long) =>
+      {
+        (arg.get_id(), long)
+      }
+      _ => continue,
+    };
+    if !arg.is_takes_value_set() {
+      if matches.is_present(id) {
+        args.push(format!("--{}", long));
+      }
+    } else if let Some(values) = matches.values_of(id) {
+      let values: Vec<&str> = values.collect();
+      args.push(format!("--{} {}", long, values.join(",")));
+    }
+  }
+  args.join(" ")
+}
@@ -1,6 +1,9 @@
 // A wrapper for CPLEX models and statuses, using cplex_sys.
 // see cplex-extern.rs for enums or other info
 
+// the unwrapcpx! macro of cplex_sys ends its calls with a unit expression
+#![allow(clippy::unused_unit)]
+
 extern crate cplex_sys;
 pub use cplex_sys::*;
 use std::ffi::CString;
@@ -21,6 +24,12 @@ pub struct Result {
   pub time: std::time::Duration, // running time
 }
 
+impl Default for Model {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
 #[allow(dead_code)]
 impl Model {
   pub fn new() -> Model {
@@ -114,21 +123,21 @@ impl Model {
     self.justsolve();
     let status = self.get_status();
     if status == Status::Feasible || status == Status::Optimal {
-      return Result {
+      Result {
         time: now.elapsed(),
         status,
         obj: self.get_obj_value(),
         nnodes: self.get_nnodes(),
         best_bound: self.get_best_bound(),
-      };
+      }
     } else {
-      return Result {
+      Result {
         time: now.elapsed(),
         status,
-        obj: std::f64::NAN,
+        obj: f64::NAN,
         nnodes: 0,
-        best_bound: std::f64::NAN,
-      };
+        best_bound: f64::NAN,
+      }
     }
   }
 
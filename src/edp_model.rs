@@ -1,6 +1,6 @@
 use crate::cpx;
 
-use crate::Instance;
+use crate::instance::Instance;
 
 pub struct EDPModel<'a> {
   model: cpx::Model,
@@ -12,10 +12,11 @@ pub struct EDPModel<'a> {
 
 #[allow(dead_code)]
 impl EDPModel<'_> {
-  pub fn new(inst: &Instance) -> EDPModel {
+  pub fn new(inst: &Instance) -> EDPModel<'_> {
     let n = inst.n;
     let mut yindex = vec![vec![0usize; n]; n];
     let mut ny = 0;
+    #[allow(clippy::needless_range_loop)]
     for i in 0..n {
       for j in (i + 1)..n {
         yindex[i][j] = ny + n;
@@ -72,6 +73,11 @@ impl EDPModel<'_> {
     res
   }
 
+  /// Sets the time limit of each solve, in seconds
+  pub fn set_time_limit(&mut self, time_secs: f64) {
+    self.model.set_time_limit(time_secs);
+  }
+
   pub fn fix(&mut self, i: usize, value: usize) {
     assert!(i < self.n);
     self.model.change_var_bounds(i, 'B', value.min(1) as f64);
@@ -137,8 +143,8 @@ impl EDPModel<'_> {
           self.model.add_row(
             0.0,
             'L',
-            &vec![e as i32, i as i32, j as i32],
-            &vec![2.0, -1.0, -1.0],
+            &[e as i32, i as i32, j as i32],
+            &[2.0, -1.0, -1.0],
           );
         }
         // y_{ij} - x_i <= 0
@@ -147,14 +153,14 @@ impl EDPModel<'_> {
           self.model.add_row(
             0.0,
             'L',
-            &vec![e as i32, i as i32],
-            &vec![1.0, -1.0],
+            &[e as i32, i as i32],
+            &[1.0, -1.0],
           );
           self.model.add_row(
             0.0,
             'L',
-            &vec![e as i32, j as i32],
-            &vec![1.0, -1.0],
+            &[e as i32, j as i32],
+            &[1.0, -1.0],
           );
         }
 
@@ -163,8 +169,8 @@ impl EDPModel<'_> {
         self.model.add_row(
           1.0,
           'L',
-          &vec![e as i32, i as i32, j as i32],
-          &vec![-1.0, 1.0, 1.0],
+          &[e as i32, i as i32, j as i32],
+          &[-1.0, 1.0, 1.0],
         );
       }
     }
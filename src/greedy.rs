@@ -1,14 +1,61 @@
 use crate::instance::Instance;
+use crate::options::Options;
 use crate::solution::Solution;
 use crate::util::ReservoirSampling;
-use crate::util::TabuList;
+use crate::util::{TabuConfig, TabuList};
+
+/// Neighborhoods explored by the tabu search
+#[derive(Clone, Copy, Debug)]
+pub struct Neighborhood {
+  pub swap: bool, // whether swap moves are used besides add/drop moves
+  pub cand: usize, // size of the candidate lists of swap moves
+  pub min_size: usize, // drop moves keep at least this many vertices
+  pub max_size: usize, // add moves keep at most this many vertices
+}
+
+impl Neighborhood {
+  /// Add/drop moves only
+  pub const FLIP: Neighborhood = Neighborhood {
+    swap: false,
+    cand: 0,
+    min_size: 2,
+    max_size: usize::MAX,
+  };
+
+  pub fn from_options(opt: &Options) -> Neighborhood {
+    let (min_size, max_size) = opt.size_bounds();
+    Neighborhood {
+      swap: opt.ts_swap,
+      cand: opt.ts_cand,
+      min_size,
+      max_size,
+    }
+  }
+}
+
+/// Returns the 'k' vertices of 'from' with the highest 'key', in no order
+pub fn candidates(
+  from: impl Iterator<Item = usize>,
+  k: usize,
+  key: impl Fn(usize) -> f64,
+) -> Vec<usize> {
+  let mut c: Vec<usize> = from.collect();
+  if c.len() > k && k > 0 {
+    c.select_nth_unstable_by(k - 1, |a, b| {
+      key(*b).partial_cmp(&key(*a)).unwrap()
+    });
+    c.truncate(k);
+  }
+  c
+}
 
 /// TODO can remove tabu if it's not effective
 pub fn ts(
   inst: &Instance,
   s: &mut Solution,
-  tenure: usize,
+  tabu_cfg: TabuConfig,
   max_iter_w_impr: usize,
+  nb: Neighborhood,
   force_in: Option<usize>, // if set, forces this vertex to be included in the solution
   core: Option<(&[usize], f64)>, // if set, ignores the vertex with index 0  and starts with length and obj equal to core's. Messes up s's obj so it contains core
 ) {
@@ -16,8 +63,45 @@ pub fn ts(
   let mut cost = vec![0.0; inst.n];
   let mut total_cost = 0.0; // does not divide
   let mut len = 0;
-  let mut tabu = TabuList::new(inst.n, tenure);
+  let mut v: Vec<usize> = Vec::with_capacity(inst.n); // vertices in the solution
+  let mut pos = vec![0; inst.n]; // position of each vertex in 'v'
+  let mut undo: Vec<usize> = Vec::new(); // flips since the incumbent
+  let mut tabu = TabuList::new(inst.n, tabu_cfg);
   let mut iter_w = 0;
+
+  macro_rules! flip {
+    ($i:expr) => {{
+      let i = $i;
+      if in_s[i] {
+        Solution::remove_shadow(
+          i,
+          inst,
+          &mut in_s,
+          &mut cost,
+          &mut total_cost,
+          &mut len,
+        );
+        let p = pos[i];
+        v.swap_remove(p);
+        if p < v.len() {
+          pos[v[p]] = p;
+        }
+      } else {
+        Solution::add_shadow(
+          i,
+          inst,
+          &mut in_s,
+          &mut cost,
+          &mut total_cost,
+          &mut len,
+        );
+        pos[i] = v.len();
+        v.push(i);
+      }
+      undo.push(i);
+    }};
+  }
+
   if s.len == 0 {
     // empty solution, start with adding highest cost edge
     let (mut bi, mut bj) = (0, 0);
@@ -29,50 +113,23 @@ pub fn ts(
         }
       }
     }
-    Solution::add_shadow(
-      bi,
-      inst,
-      &mut in_s,
-      &mut cost,
-      &mut total_cost,
-      &mut len,
-    );
-    Solution::add_shadow(
-      bj,
-      inst,
-      &mut in_s,
-      &mut cost,
-      &mut total_cost,
-      &mut len,
-    );
+    flip!(bi);
+    flip!(bj);
   } else {
     // TODO instead of adding all vertices, copy s.c to cost!
     // add all vertices in s
     for i in &s.v {
-      Solution::add_shadow(
-        *i,
-        inst,
-        &mut in_s,
-        &mut cost,
-        &mut total_cost,
-        &mut len,
-      );
+      flip!(*i);
     }
   }
 
   if let Some(u) = force_in {
     // force add 'force_in'
     if !in_s[u] {
-      Solution::add_shadow(
-        u,
-        inst,
-        &mut in_s,
-        &mut cost,
-        &mut total_cost,
-        &mut len,
-      );
+      flip!(u);
     }
   }
+  undo.clear();
 
   if let Some((core, core_cost)) = core {
     assert!(in_s[0]); // if there's a 'core', then vertex 0 (representing the core) must be in the initial solution
@@ -80,89 +137,158 @@ pub fn ts(
     total_cost += core_cost;
   }
 
+  // bring the size within the bounds of 'nb', adding the vertices with the
+  // largest costs or dropping those with the smallest ones
+  let first = core.is_some() as usize; // 1 if core, 0 if no core
+  while len < nb.min_size {
+    let i = (first..inst.n)
+      .filter(|i| !in_s[*i])
+      .max_by(|a, b| cost[*a].partial_cmp(&cost[*b]).unwrap());
+    match i {
+      Some(i) => flip!(i),
+      None => break,
+    }
+  }
+  while len > nb.max_size {
+    let i = v
+      .iter()
+      .cloned()
+      .filter(|i| *i >= first && force_in != Some(*i))
+      .min_by(|a, b| cost[*a].partial_cmp(&cost[*b]).unwrap());
+    match i {
+      Some(i) => flip!(i),
+      None => break,
+    }
+  }
+  undo.clear();
+
   let mut inc_obj = total_cost / len as f64; // incumbent's objective value
 
   loop {
-    let mut best_i = inst.n; // index of best move
-    let mut best_obj = std::f64::MIN; // cost of best move
-    let first = core.is_some() as usize; // 1 if core, 0 if no core
+    let mut best_i = inst.n; // index of best flip move
+    let mut best_obj = f64::MIN; // cost of best move
+    let mut best_score = f64::MIN; // cost of best move, with penalties
     for i in first..inst.n {
-      if tabu.is_tabu(i) {
-        continue; // don't touch tabu vertices
-      }
       if let Some(u) = force_in {
         if i == u {
           continue; // don't touch the vertex who is 'forced_in'
         }
       }
       let obj_i = if in_s[i] {
-        if len > 1 {
+        if len > nb.min_size {
           (total_cost - cost[i]) / (len as f64 - 1.0) // cost to remove
         } else {
-          std::f64::MIN // can't remove all vertices
+          f64::MIN // can't go below the minimum size
         }
-      } else {
+      } else if len < nb.max_size {
         (total_cost + cost[i]) / (len as f64 + 1.0) // cost to add
+      } else {
+        continue; // can't go above the maximum size
       };
-      if obj_i > best_obj {
+      if tabu.is_tabu(i) && !tabu.aspires(obj_i, inc_obj) {
+        continue; // don't touch tabu vertices
+      }
+      let score = obj_i - tabu.penalty(i);
+      if score > best_score {
+        best_score = score;
         best_obj = obj_i;
         best_i = i;
       }
     }
 
-    tabu.advance_iter();
-    if best_i == inst.n {
-      continue; // this can happen if all moves are tabu
-    }
-    if in_s[best_i] {
-      Solution::remove_shadow(
-        best_i,
-        inst,
-        &mut in_s,
-        &mut cost,
-        &mut total_cost,
-        &mut len,
+    // swap moves (drop u, add w), evaluated in O(1) over the candidate lists
+    // of the cheapest vertices to drop and the best vertices to add
+    let mut best_swap = None;
+    if nb.swap {
+      let movable = |i: usize| {
+        i >= first
+          && force_in != Some(i)
+          && (tabu_cfg.aspiration || !tabu.is_tabu(i))
+      };
+      let drop =
+        candidates(v.iter().cloned().filter(|i| movable(*i)), nb.cand, |i| {
+          -cost[i]
+        });
+      let add = candidates(
+        (0..inst.n).filter(|i| !in_s[*i] && movable(*i)),
+        nb.cand,
+        |i| cost[i],
       );
+      for u in &drop {
+        for w in &add {
+          let obj =
+            (total_cost - cost[*u] + cost[*w] - inst.dist(*u, *w)) / len as f64;
+          if (tabu.is_tabu(*u) || tabu.is_tabu(*w))
+            && !tabu.aspires(obj, inc_obj)
+          {
+            continue;
+          }
+          let score = obj - tabu.penalty(*u) - tabu.penalty(*w);
+          if score > best_score {
+            best_score = score;
+            best_obj = obj;
+            best_swap = Some((*u, *w));
+          }
+        }
+      }
+    }
+
+    tabu.advance_iter();
+    if let Some((u, w)) = best_swap {
+      flip!(u);
+      flip!(w);
+      tabu.add(u, false);
+      tabu.add(w, true);
+    } else if best_i == inst.n {
+      // all moves are tabu, or there are none: count it as a non-improving
+      // iteration, so that the search ends even if that lasts
+      iter_w += 1;
+      if iter_w >= max_iter_w_impr {
+        break;
+      }
+      continue;
     } else {
-      Solution::add_shadow(
-        best_i,
-        inst,
-        &mut in_s,
-        &mut cost,
-        &mut total_cost,
-        &mut len,
-      );
+      flip!(best_i);
+      tabu.add(best_i, in_s[best_i]);
     }
-    tabu.add(best_i);
     assert!(eq!(best_obj, total_cost / len as f64));
 
     let improved = gr!(best_obj, inc_obj);
 
     if improved {
-      // TODO if tabu list is disabled, only update s at the end!
       inc_obj = best_obj;
-      s.total_cost = total_cost;
-      s.has = in_s.clone();
-      s.cost = cost.clone();
-      s.v = (0..inst.n).filter(|x| in_s[*x]).collect();
-      s.len = s.v.len();
-      if let Some((_, core_cost)) = core {
-        s.total_cost -= core_cost;
-      }
-      // assert!(eq!(s.get_obj_bruteforce(inst), s.obj));
+      undo.clear();
+      tabu.improved();
       iter_w = 0;
     } else {
+      tabu.stagnate();
       iter_w += 1;
       if iter_w >= max_iter_w_impr {
         break;
       }
     }
   }
+
+  // go back to the incumbent
+  for i in std::mem::take(&mut undo).into_iter().rev() {
+    flip!(i);
+  }
+  v.sort_unstable();
+  s.total_cost = total_cost;
+  s.len = v.len();
+  s.v = v;
+  s.has = in_s;
+  s.cost = cost;
+  if let Some((_, core_cost)) = core {
+    s.total_cost -= core_cost;
+  }
+  // assert!(eq!(s.get_obj_bruteforce(inst), s.obj));
 }
 
-/// Given a solution s and a size sz, returns a list of sz pairs (v, obj),
-/// indicating a greedy order of vertices v to be added to s, with intermediate
-/// objective values obj.
+/// Given a solution s and a size sz, returns a list of sz vertices, in a
+/// greedy order to be added to s. Vertices are chosen by their attraction to
+/// the solution under its objective (Objective::attraction): their sum of
+/// distances to it for the max-mean.
 pub fn insertion_order(
   inst: &Instance,
   s: &Solution,
@@ -176,65 +302,37 @@ pub fn insertion_order(
   }
 
   let mut ans = Vec::with_capacity(sz);
-  let mut in_s = vec![false; inst.n];
-  let mut cost_to_add = vec![0.0; inst.n];
-
-  // if there's a tabu list, mark tabu vertices "in s" so they won't be
-  // selected. this is kind of a hack.
-  if let Some(tl) = tabu {
-    for i in 0..inst.n {
-      in_s[i] = tl.is_tabu(i);
-    }
-  }
+  // the solution with the vertices added so far, which keeps their keys
+  let mut t = s.clone();
+  // if there's a tabu list, tabu vertices won't be selected
+  let free = |i: usize| tabu.is_none() || !tabu.unwrap().is_tabu(i);
 
-  macro_rules! add {
-    ($i:expr) => {{
-      in_s[$i] = true;
-      for j in 0..inst.n {
-        if !in_s[j] {
-          cost_to_add[j] += inst.dist($i, j);
-        }
-      }
-    }};
-  }
   if s.len == 0 {
     // start with highest cost edge
     let (mut bi, mut bj) = (0, 0);
-    for i in 0..inst.n {
-      if in_s[i] {
-        continue;
-      }
-      for j in (i + 1)..inst.n {
-        if !in_s[j] && inst.dist(i, j) > inst.dist(bi, bj) {
+    for i in (0..inst.n).filter(|i| free(*i)) {
+      for j in ((i + 1)..inst.n).filter(|j| free(*j)) {
+        if inst.dist(i, j) > inst.dist(bi, bj) {
           bi = i;
           bj = j;
         }
       }
     }
-    add!(bi);
+    t.add(bi);
     ans.push(bi);
     if sz >= 2 {
-      add!(bj);
+      t.add(bj);
       ans.push(bj);
     }
-  } else {
-    cost_to_add.copy_from_slice(&s.cost);
-    // add all
-    for i in &s.v {
-      in_s[*i] = true;
-      //   add!(*i);
-    }
   }
   while ans.len() < sz {
+    let key = |i: usize| t.objective.attraction(&t, i);
     let mut best = inst.n;
-    let mut best_cost = std::f64::MIN;
+    let mut best_cost = f64::MIN;
     for i in 0..inst.n {
-      if !in_s[i]
-        && (tabu.is_none() || !tabu.unwrap().is_tabu(i))
-        && cost_to_add[i] > best_cost
-      {
+      if !t.has[i] && free(i) && key(i) > best_cost {
         best = i;
-        best_cost = cost_to_add[i];
+        best_cost = key(i);
       }
     }
     if best == inst.n {
@@ -245,23 +343,23 @@ pub fn insertion_order(
     if alpha > 0.0 {
       let mut rs = ReservoirSampling::new();
       for i in 0..inst.n {
-        if !in_s[i]
-          && (tabu.is_none() || !tabu.unwrap().is_tabu(i))
-          && (best_cost - cost_to_add[i]) / best_cost < alpha
+        if !t.has[i]
+          && free(i)
+          && (best_cost - key(i)) / best_cost < alpha
           && rs.consider()
         {
           j = i;
         }
       }
     }
-    add!(j);
+    t.add(j);
     ans.push(j);
   }
   ans
 }
 
 /// Same as insertion_order, but for removal; here, sz is the number f vertices
-/// that should be removed.
+/// that should be removed, those of least attraction first.
 pub fn removal_order(
   inst: &Instance,
   s: &Solution,
@@ -276,40 +374,22 @@ pub fn removal_order(
   assert!(s.len > 0);
 
   let mut ans = Vec::with_capacity(sz);
-  let mut in_s = vec![false; inst.n];
-  let mut cost_to_remove = vec![0.0; inst.n];
-
-  // add all
-  for i in &s.v {
-    in_s[*i] = true;
-    for j in &s.v {
-      cost_to_remove[*i] += inst.dist(*i, *j);
-    }
-    // if there's a tabu list, mark tabu vertices "not in s" so they won't be
-    // selected. this is kind of a hac
-    if let Some(tl) = tabu {
-      in_s[*i] = !tl.is_tabu(*i);
-    }
-  }
-
-  macro_rules! remove {
-    ($i:expr) => {{
-      assert!(in_s[$i]);
-      in_s[$i] = false;
-      for j in 0..inst.n {
-        // TODO this could be optimized by keeping s as a list
-        cost_to_remove[j] -= inst.dist($i, j);
-      }
-    }};
-  }
+  // the solution without the vertices removed so far, which keeps their keys
+  let mut t = s.clone();
 
   while ans.len() < sz {
+    // if there's a tabu list, tabu vertices won't be selected; candidates
+    // are scanned in the order of 's', which removals don't change
+    let free =
+      |i: usize| t.has[i] && (tabu.is_none() || !tabu.unwrap().is_tabu(i));
+    let key = |i: usize| t.objective.attraction(&t, i);
     let mut best = inst.n;
-    let mut best_cost = std::f64::MAX;
-    for i in &s.v {
-      if in_s[*i] && cost_to_remove[*i] < best_cost {
-        best_cost = cost_to_remove[*i];
-        best = *i;
+    let mut best_cost = f64::MAX;
+    for i in s.v.iter().cloned().filter(|i| free(*i)) {
+      let k = key(i);
+      if k < best_cost {
+        best_cost = k;
+        best = i;
       }
     }
     if best == inst.n {
@@ -318,17 +398,108 @@ pub fn removal_order(
     let mut j = best;
     if alpha > 0.0 {
       let mut rs = ReservoirSampling::new();
-      for i in &s.v {
-        if in_s[*i]
-          && (cost_to_remove[*i] - best_cost) / best_cost < alpha
-          && rs.consider()
-        {
-          j = *i;
+      for i in s.v.iter().cloned().filter(|i| free(*i)) {
+        if (key(i) - best_cost) / best_cost < alpha && rs.consider() {
+          j = i;
         }
       }
     }
-    remove!(j);
+    t.remove(j);
     ans.push(j);
   }
   ans
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::{solution, tiny};
+
+  /// Best mean over the subsets with a size in [lo, hi], by enumeration
+  fn best_mean(inst: &Instance, lo: usize, hi: usize) -> f64 {
+    let mut best = f64::MIN;
+    for mask in 0usize..1 << inst.n {
+      let v: Vec<usize> = (0..inst.n).filter(|i| mask >> i & 1 == 1).collect();
+      if v.len() < lo.max(2) || v.len() > hi {
+        continue;
+      }
+      let mut sum = 0.0;
+      for (a, &u) in v.iter().enumerate() {
+        for &w in &v[..a] {
+          sum += inst.dist(u, w);
+        }
+      }
+      best = best.max(sum / v.len() as f64);
+    }
+    best
+  }
+
+  /// Asserts that 's' is what recompute_from_v makes of its vertices
+  fn assert_consistent(s: &Solution) {
+    let mut t = s.clone();
+    t.recompute_from_v();
+    assert_eq!(s.len, t.len);
+    assert!(eq!(s.total_cost, t.total_cost));
+    assert_eq!(s.has, t.has);
+    for (a, b) in s.cost.iter().zip(&t.cost) {
+      assert!(eq!(*a, *b));
+    }
+  }
+
+  fn run(inst: &Instance, s: &mut Solution, nb: Neighborhood) {
+    ts(inst, s, TabuConfig::fixed(1), 20, nb, None, None);
+  }
+
+  #[test]
+  fn ts_returns_the_incumbent() {
+    let inst = tiny();
+    for start in [&[2, 4, 6][..], &[0, 1, 2, 3, 4, 5, 6]] {
+      let mut s = solution(&inst, start);
+      let start_obj = s.obj();
+      run(&inst, &mut s, Neighborhood::FLIP);
+      // the search ends on non-improving moves, which are undone
+      assert_consistent(&s);
+      assert!(s.obj() >= start_obj);
+      assert!(eq!(s.obj(), best_mean(&inst, 2, inst.n)));
+    }
+  }
+
+  #[test]
+  fn ts_swaps_match_recompute() {
+    // with the size fixed, only swap moves apply; ts asserts that the value
+    // of each move it makes is the mean it gets
+    let inst = tiny();
+    for sz in 2..inst.n {
+      let nb = Neighborhood {
+        swap: true,
+        cand: 0,
+        min_size: sz,
+        max_size: sz,
+      };
+      let mut s = solution(&inst, &(0..sz).collect::<Vec<_>>());
+      run(&inst, &mut s, nb);
+      assert_consistent(&s);
+      assert_eq!(s.len, sz);
+      assert!(eq!(s.obj(), best_mean(&inst, sz, sz)));
+    }
+  }
+
+  #[test]
+  fn ts_keeps_size_bounds() {
+    let inst = tiny();
+    for (lo, hi) in [(2, 3), (4, 5), (5, usize::MAX)] {
+      for start in [&[0, 1][..], &[1, 3, 5], &[0, 1, 2, 3, 4, 5, 6]] {
+        let nb = Neighborhood {
+          min_size: lo,
+          max_size: hi,
+          ..Neighborhood::FLIP
+        };
+        let mut s = solution(&inst, start);
+        run(&inst, &mut s, nb);
+        assert_consistent(&s);
+        assert!(lo <= s.len && s.len <= hi);
+        assert!(eq!(s.obj(), best_mean(&inst, lo, hi)));
+      }
+    }
+  }
+}
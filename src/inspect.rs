@@ -0,0 +1,174 @@
+// Instance statistics (inspect subcommand): the distribution of the
+// distances and of the row sums, and estimates of the size of the optimal
+// solution, from the vertices with positive row sums and from greedy
+// solutions, to characterize instances before choosing an algorithm.
+
+use crate::auto_params::Features;
+use crate::bench::glob;
+use crate::instance::Instance;
+use crate::options::{Format, InspectOptions, Options, Parser};
+use crate::stats;
+use crate::vlns;
+
+/// Statistics of an instance
+struct Stats {
+  instance: String,
+  features: Features,
+  negative: f64, // share of negative distances
+  zero: f64,     // share of zero distances
+  row_min: f64,  // row sums: the total distance of a vertex to all others
+  row_mean: f64,
+  row_std: f64,
+  row_max: f64,
+  positive_rows: f64, // share of vertices with a positive row sum
+  greedy_min: usize,  // sizes of greedy solutions from random vertices
+  greedy_avg: f64,
+  greedy_max: usize,
+  greedy_value: f64, // best value of the greedy solutions
+}
+
+impl Stats {
+  fn of(instance: &str, inst: &Instance, i: &InspectOptions) -> Stats {
+    let pairs = (inst.n * (inst.n - 1) / 2) as f64;
+    let (mut negative, mut zero) = (0.0, 0.0);
+    for u in 0..inst.n {
+      for v in (u + 1)..inst.n {
+        let d = inst.dist(u, v);
+        negative += (d < 0.0) as usize as f64;
+        zero += (d == 0.0) as usize as f64;
+      }
+    }
+    let rows: Vec<f64> =
+      (0..inst.n).map(|u| (0..inst.n).map(|v| inst.dist(u, v)).sum()).collect();
+
+    // greedy solutions, as the initial solutions of the heuristic
+    let opt = Options::parse_from(["maxmeandp-vlns", "-i", instance]);
+    let mut starts: Vec<usize> = (0..inst.n).collect();
+    fastrand::shuffle(&mut starts);
+    let (mut sizes, mut best) = (Vec::new(), f64::MIN);
+    for &s in starts.iter().take(i.starts.max(1)) {
+      let s = vlns::initial_solution(inst, s, &opt);
+      sizes.push(s.len);
+      best = best.max(s.obj());
+    }
+
+    let name = std::path::Path::new(instance).file_stem().unwrap();
+    Stats {
+      instance: name.to_string_lossy().to_string(),
+      features: Features::of(inst),
+      negative: negative / pairs,
+      zero: zero / pairs,
+      row_min: rows.iter().cloned().fold(f64::MAX, f64::min),
+      row_mean: stats::mean(&rows),
+      row_std: stats::std_dev(&rows),
+      row_max: rows.iter().cloned().fold(f64::MIN, f64::max),
+      positive_rows: rows.iter().filter(|&&r| r > 0.0).count() as f64
+        / inst.n as f64,
+      greedy_min: *sizes.iter().min().unwrap(),
+      greedy_avg: sizes.iter().sum::<usize>() as f64 / sizes.len() as f64,
+      greedy_max: *sizes.iter().max().unwrap(),
+      greedy_value: best,
+    }
+  }
+}
+
+const COLUMNS: &[&str] = &[
+  "instance", "n", "pos", "neg", "zero", "mean", "std", "skew", "row_min",
+  "row_mean", "row_std", "row_max", "pos_rows", "gr_min", "gr_avg", "gr_max",
+  "gr_value",
+];
+
+/// The values of the columns of 's', formatted
+fn values(s: &Stats) -> Vec<String> {
+  let f = &s.features;
+  vec![
+    s.instance.clone(),
+    f.n.to_string(),
+    format!("{:.4}", f.density),
+    format!("{:.4}", s.negative),
+    format!("{:.4}", s.zero),
+    format!("{:.4}", f.mean),
+    format!("{:.4}", f.std),
+    format!("{:.4}", f.skew),
+    format!("{:.2}", s.row_min),
+    format!("{:.2}", s.row_mean),
+    format!("{:.2}", s.row_std),
+    format!("{:.2}", s.row_max),
+    format!("{:.4}", s.positive_rows),
+    s.greedy_min.to_string(),
+    format!("{:.1}", s.greedy_avg),
+    s.greedy_max.to_string(),
+    format!("{:.4}", s.greedy_value),
+  ]
+}
+
+fn header(format: Format) -> Option<String> {
+  match format {
+    Format::Table => {
+      let h: Vec<String> = COLUMNS
+        .iter()
+        .enumerate()
+        .map(|(k, c)| format!("{:>w$}", c, w = width(k)))
+        .collect();
+      Some(h.join(" "))
+    }
+    Format::Csv => Some(COLUMNS.join(",")),
+    Format::Json => None,
+  }
+}
+
+/// Width of column 'k' in tables
+fn width(k: usize) -> usize {
+  if k == 0 {
+    12
+  } else {
+    COLUMNS[k].len().max(8)
+  }
+}
+
+fn format_row(s: &Stats, format: Format) -> String {
+  let v = values(s);
+  match format {
+    Format::Table => {
+      let v: Vec<String> = v
+        .iter()
+        .enumerate()
+        .map(|(k, x)| format!("{:>w$}", x, w = width(k)))
+        .collect();
+      v.join(" ")
+    }
+    Format::Csv => v.join(","),
+    Format::Json => {
+      let kv: Vec<String> = COLUMNS
+        .iter()
+        .zip(&v)
+        .enumerate()
+        .map(|(k, (c, x))| {
+          if k == 0 {
+            format!("\"{}\":\"{}\"", c, x)
+          } else {
+            format!("\"{}\":{}", c, x)
+          }
+        })
+        .collect();
+      format!("{{{}}}", kv.join(","))
+    }
+  }
+}
+
+/// Runs the inspect subcommand: prints a row of statistics per instance
+pub fn run(i: &InspectOptions) {
+  fastrand::seed(i.seed);
+  let instances = glob(&i.instances);
+  if instances.is_empty() {
+    panic!("No instances match \"{}\"", i.instances);
+  }
+  if let Some(h) = header(i.format) {
+    println!("{}", h);
+  }
+  for path in instances {
+    let path = path.to_str().unwrap();
+    let inst = Instance::read_from_file(path);
+    println!("{}", format_row(&Stats::of(path, &inst, i), i.format));
+  }
+}
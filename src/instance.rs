@@ -1,21 +1,66 @@
+/// Storage type of the distances; single precision with the "f32" feature
+#[cfg(not(feature = "f32"))]
+pub type Weight = f64;
+#[cfg(feature = "f32")]
+pub type Weight = f32;
+
 /// Problem instance
 pub struct Instance {
   pub n: usize,
-  d: Vec<f64>,
+  d: Vec<Weight>, // row-major, so each row is contiguous
 }
 impl Instance {
   /// Get distance
+  #[allow(clippy::unnecessary_cast)]
   pub fn dist(&self, i: usize, j: usize) -> f64 {
-    *unsafe { self.d.get_unchecked(i * self.n + j) }
+    *unsafe { self.d.get_unchecked(i * self.n + j) } as f64
+  }
+
+  /// Distances from 'i' to all vertices
+  pub fn row(&self, i: usize) -> &[Weight] {
+    &self.d[i * self.n..(i + 1) * self.n]
+  }
+
+  /// Adds the distances from 'u' to 'acc', element-wise. Written over
+  /// slices, so that it is vectorized.
+  #[allow(clippy::unnecessary_cast)]
+  pub fn add_row(&self, u: usize, acc: &mut [f64]) {
+    for (a, d) in acc.iter_mut().zip(self.row(u)) {
+      *a += *d as f64;
+    }
+  }
+
+  /// Subtracts the distances from 'u' from 'acc', element-wise
+  #[allow(clippy::unnecessary_cast)]
+  pub fn sub_row(&self, u: usize, acc: &mut [f64]) {
+    for (a, d) in acc.iter_mut().zip(self.row(u)) {
+      *a -= *d as f64;
+    }
   }
 
   pub fn new(n: usize, d: Vec<f64>) -> Instance {
+    #[cfg(feature = "f32")]
+    let d = d.into_iter().map(|x| x as Weight).collect();
     Instance {
       n,
       d,
     }
   }
 
+  /// Creates an instance with distances drawn uniformly from [-10, 10], as
+  /// the type I instances of Brimberg et al.
+  pub fn random(n: usize) -> Instance {
+    let mut d = vec![0.0; n * n];
+    for i in 0..n {
+      for j in (i + 1)..n {
+        let dij = fastrand::f64() * 20.0 - 10.0;
+        d[i * n + j] = dij;
+        d[j * n + i] = dij;
+      }
+    }
+    Instance::new(n, d)
+  }
+
   /// Read problem instance
   pub fn read_from_file(filename: &str) -> Instance {
     use std::fs;
@@ -32,7 +77,7 @@ impl Instance {
       let msg =
         &format!("Invalid input in line {}: \"{}\"; check the instance", i, l);
       let t = (
-        ll.get(0).expect(msg).parse().expect(msg),
+        ll.first().expect(msg).parse().expect(msg),
         ll.get(1).expect(msg).parse().expect(msg),
         ll.get(2).expect(msg).parse().expect(msg),
       );
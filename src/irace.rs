@@ -0,0 +1,341 @@
+// Integration with irace: the parameter space of the tuned options, written
+// from their definitions in options.rs (irace-params subcommand), and a
+// target runner that solves an instance with a candidate configuration
+// (target-runner subcommand).
+
+use crate::instance::Instance;
+use crate::options::{
+  IraceParamsOptions, Options, Parser, TargetRunnerOptions,
+};
+use crate::vlns;
+use clap::{Arg, CommandFactory, ErrorKind};
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+
+/// Domain of a tuned parameter
+enum Domain {
+  Int(i64, i64),
+  Real(f64, f64),
+  Ordinal(&'static [&'static str]),
+  /// The possible values of the option, or false and true for booleans
+  Categorical,
+  /// The given values, unordered
+  Choice(&'static [&'static str]),
+}
+
+/// A tuned option
+struct Param {
+  name: &'static str, // name in irace
+  long: &'static str, // long name of the option
+  domain: Domain,
+  /// Enabled unless the parameter (first) has the value (second)
+  unless: Option<(&'static str, &'static str)>,
+  /// Value of the default configuration, if not the default of the option
+  default: Option<&'static str>,
+}
+
+/// Value of a parameter that leaves its option out of the command line, for
+/// options whose default depends on others (--tenure-drop)
+const SAME: &str = "same";
+
+const fn param(
+  name: &'static str,
+  long: &'static str,
+  domain: Domain,
+) -> Param {
+  Param {
+    name,
+    long,
+    domain,
+    unless: None,
+    default: None,
+  }
+}
+
+/// The tuned options; their types and switches come from Options. The default
+/// configuration is the one calibrated by hand before tuning, with the
+/// defaults of the options for the parameters added since.
+const PARAMS: &[Param] = &[
+  Param {
+    default: Some("250"),
+    ..param(
+      "iterwoimp",
+      "max-iter-wo-impr",
+      Domain::Ordinal(&["50", "100", "250", "500", "1000", "2500", "5000"]),
+    )
+  },
+  Param {
+    default: Some("5"),
+    ..param(
+      "tenure",
+      "tenure",
+      Domain::Ordinal(&["0", "3", "5", "8", "12", "17"]),
+    )
+  },
+  Param {
+    default: Some(SAME),
+    ..param(
+      "tenuredrop",
+      "tenure-drop",
+      Domain::Choice(&[SAME, "0", "3", "5", "8", "12", "17"]),
+    )
+  },
+  param("tstenure", "ts-tenure", Domain::Ordinal(&["1", "2", "3", "5"])),
+  param("tenurerand", "tenure-rand", Domain::Ordinal(&["0", "2", "5", "10"])),
+  param(
+    "tenuregrowth",
+    "tenure-growth",
+    Domain::Ordinal(&["0", "1", "2", "5"]),
+  ),
+  param("aspiration", "aspiration", Domain::Categorical),
+  param(
+    "freqpenalty",
+    "freq-penalty",
+    Domain::Ordinal(&["0", "0.5", "1", "2", "5"]),
+  ),
+  Param {
+    default: Some("30"),
+    ..param(
+      "sz",
+      "subp-sz",
+      Domain::Ordinal(&["30", "60", "70", "80", "90", "100"]),
+    )
+  },
+  Param {
+    default: Some("5"),
+    ..param(
+      "maxshakes",
+      "max-shakes",
+      Domain::Ordinal(&["0", "2", "5", "10", "25", "50"]),
+    )
+  },
+  Param {
+    default: Some("0.25"),
+    ..param(
+      "shakesize",
+      "shake-size",
+      Domain::Ordinal(&["0.1", "0.2", "0.25", "0.3", "0.4"]),
+    )
+  },
+  Param {
+    default: Some("0.2"),
+    ..param(
+      "shakealpha",
+      "shake-alpha",
+      Domain::Ordinal(&["0.1", "0.2", "0.3"]),
+    )
+  },
+  Param {
+    default: Some("0.2"),
+    ..param("subpalpha", "subp-alpha", Domain::Ordinal(&["0.1", "0.2", "0.3"]))
+  },
+  param(
+    "subpgrowth",
+    "subp-growth",
+    Domain::Ordinal(&["0", "0.01", "0.02", "0.05"]),
+  ),
+  param("subprestarts", "subp-restarts", Domain::Int(1, 3)),
+  param("prevery", "pr-every", Domain::Ordinal(&["0", "5", "10", "25"])),
+  Param {
+    unless: Some(("prevery", "0")),
+    ..param("elitesize", "elite-size", Domain::Int(5, 20))
+  },
+  Param {
+    unless: Some(("prevery", "0")),
+    ..param("elitemindist", "elite-min-dist", Domain::Real(0.0, 0.3))
+  },
+];
+
+/// Type and values of a parameter in irace's syntax
+fn domain(p: &Param, arg: &Arg) -> (char, Vec<String>) {
+  match p.domain {
+    Domain::Int(a, b) => ('i', vec![a.to_string(), b.to_string()]),
+    Domain::Real(a, b) => ('r', vec![a.to_string(), b.to_string()]),
+    Domain::Ordinal(v) => ('o', v.iter().map(|v| v.to_string()).collect()),
+    Domain::Choice(v) => ('c', v.iter().map(|v| v.to_string()).collect()),
+    Domain::Categorical => match arg.get_possible_values() {
+      Some(v) => ('c', v.iter().map(|v| v.get_name().to_string()).collect()),
+      None => ('c', vec!["false".to_string(), "true".to_string()]),
+    },
+  }
+}
+
+/// A tuned option, with its domain in irace's syntax
+pub struct Tuned {
+  pub name: &'static str,
+  pub long: &'static str,
+  pub kind: char, // 'i'nteger, 'r'eal, 'o'rdinal or 'c'ategorical
+  pub values: Vec<String>, // bounds of integers and reals, else the values
+  pub unless: Option<(usize, &'static str)>, // disabled if this index has it
+  pub default: String, // "NA" if disabled by default
+}
+
+impl Tuned {
+  /// Whether 'value' is in the domain
+  pub fn contains(&self, value: &str) -> bool {
+    match self.kind {
+      'i' | 'r' => {
+        let v: f64 = value.parse().unwrap_or(f64::NAN);
+        self.values[0].parse::<f64>().unwrap() <= v
+          && v <= self.values[1].parse::<f64>().unwrap()
+      }
+      _ => self.values.iter().any(|x| x == value),
+    }
+  }
+}
+
+/// The space of tuned options. Panics if a tuned option doesn't exist, takes
+/// no value, or has a default outside of its domain, so that the space can't
+/// get out of sync with the options.
+pub fn space() -> Vec<Tuned> {
+  let cmd = Options::command();
+  let mut space: Vec<Tuned> = Vec::new();
+  for p in PARAMS {
+    let arg = cmd
+      .get_arguments()
+      .find(|a| a.get_long() == Some(p.long))
+      .unwrap_or_else(|| panic!("No option --{}", p.long));
+    if !arg.is_takes_value_set() {
+      panic!("Option --{} takes no value and can't be tuned", p.long);
+    }
+    let (kind, values) = domain(p, arg);
+    let unless = p.unless.map(|(q, v)| {
+      (space.iter().position(|t| t.name == q).expect("Unknown condition"), v)
+    });
+    let mut t = Tuned {
+      name: p.name,
+      long: p.long,
+      kind,
+      values,
+      unless,
+      default: match (p.default, arg.get_default_values().first()) {
+        (Some(d), _) => d.to_string(),
+        (None, Some(d)) => d.to_string_lossy().to_string(),
+        (None, None) => panic!("No default value of --{}", p.long),
+      },
+    };
+    if unless.is_some_and(|(q, v)| space[q].default == v) {
+      t.default = "NA".to_string();
+    } else if !t.contains(&t.default) {
+      panic!("Default {} of --{} is out of its domain", t.default, p.long);
+    }
+    space.push(t);
+  }
+  space
+}
+
+/// Command-line options of a configuration, a value per tuned option
+pub fn config_args(space: &[Tuned], config: &[String]) -> Vec<String> {
+  let mut args = Vec::new();
+  for (t, v) in space.iter().zip(config) {
+    if v != "NA" && v != SAME {
+      args.push(format!("--{}", t.long));
+      args.push(v.clone());
+    }
+  }
+  args
+}
+
+/// Configurations in the format of irace's --candidates-file: a line with
+/// the parameter names, then a line per configuration
+pub fn format_configs(space: &[Tuned], configs: &[Vec<String>]) -> String {
+  let names: Vec<&str> = space.iter().map(|t| t.name).collect();
+  let mut out = names.join(" ") + "\n";
+  for c in configs {
+    out += &(c.join(" ") + "\n");
+  }
+  out
+}
+
+/// Options of a run of this program on 'instance' with the options 'args',
+/// or why they're invalid
+pub fn run_options(
+  instance: &str,
+  seed: u64,
+  time_limit: u64,
+  args: &[String],
+) -> Result<Options, String> {
+  let mut argv: Vec<String> = [
+    "maxmeandp-vlns",
+    "--irace",
+    "-i",
+    instance,
+    "-s",
+    &seed.to_string(),
+    "-t",
+    &time_limit.to_string(),
+  ]
+  .iter()
+  .map(|a| a.to_string())
+  .collect();
+  argv.extend(args.iter().cloned());
+  let opt = Options::try_parse_from(argv).map_err(|e| e.to_string())?;
+  opt.check()?;
+  Ok(opt)
+}
+
+/// Runs the irace-params subcommand: writes parameters.txt and default.txt
+pub fn write_params(p: &IraceParamsOptions) {
+  let space = space();
+  let mut params = String::from(
+    "### Parameters of maxmeandp-vlns, written by its irace-params subcommand\n\
+     ### from the definitions of its options; don't edit by hand\n\n\
+     # name\t\tswitch\t\ttype\tvalues\t\t[conditions (using R syntax)]\n",
+  );
+  for t in &space {
+    let condition = t.unless.map_or(String::new(), |(q, v)| {
+      format!(" | {} != \"{}\"", space[q].name, v)
+    });
+    params += &format!(
+      "{} \"--{} \" {} ({}){}\n",
+      t.name,
+      t.long,
+      t.kind,
+      t.values.join(", "),
+      condition
+    );
+  }
+  let defaults: Vec<String> = space.iter().map(|t| t.default.clone()).collect();
+  let default = format!(
+    "## Default configuration of maxmeandp-vlns, for irace's --candidates-file;\n\
+     ## written by its irace-params subcommand\n{}",
+    format_configs(&space, &[defaults])
+  );
+  let dir = Path::new(&p.out_dir);
+  fs::create_dir_all(dir).expect("Unable to create irace directory");
+  fs::write(dir.join("parameters.txt"), params)
+    .and_then(|_| fs::write(dir.join("default.txt"), default))
+    .expect("Unable to write irace files");
+}
+
+/// Runs the target-runner subcommand: solves the instance with the options
+/// of the configuration, and prints its cost (the negated objective, as irace
+/// minimizes) and the time taken, for irace's capping and time budgets
+pub fn target_runner(r: &TargetRunnerOptions) {
+  let start = Instant::now();
+  let mut args = r.args.iter();
+  let mut time_limit = r.time_limit;
+  if r.capping {
+    let bound: f64 = args
+      .next()
+      .and_then(|b| b.parse().ok())
+      .expect("Missing time bound of capping");
+    time_limit = time_limit.min(bound.ceil() as u64).max(1);
+  }
+  // irace passes the switch of every enabled parameter, even with SAME
+  let mut args: Vec<String> = args.cloned().collect();
+  while let Some(k) = args.iter().position(|a| a == SAME) {
+    args.drain(k.saturating_sub(1)..=k);
+  }
+  let opt =
+    run_options(&r.instance, r.seed, time_limit, &args).unwrap_or_else(|msg| {
+      Options::command().error(ErrorKind::ValueValidation, msg).exit()
+    });
+  fastrand::seed(opt.seed);
+
+  let inst = Instance::read_from_file(opt.instance_file());
+  opt.validate_instance(inst.n);
+  let (s, _) = vlns::solve(&inst, &opt);
+  println!("{:.6} {:.6}", -s.obj(), start.elapsed().as_secs_f64());
+}
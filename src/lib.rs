@@ -0,0 +1,33 @@
+#![allow(dead_code)]
+
+#[macro_use]
+pub mod ff;
+
+pub mod alns;
+pub mod auto_params;
+pub mod bench;
+pub mod bks;
+pub mod compare;
+pub mod config;
+#[allow(dead_code)]
+pub mod cpx;
+pub mod edp_model;
+pub mod greedy;
+pub mod inspect;
+pub mod instance;
+pub mod irace;
+pub mod memetic;
+pub mod objective;
+pub mod options;
+pub mod preprocess;
+pub mod profile;
+pub mod relink;
+pub mod solution;
+pub mod stats;
+#[cfg(test)]
+mod testing;
+pub mod trace;
+pub mod tune;
+#[allow(dead_code)]
+pub mod util;
+pub mod vlns;
@@ -0,0 +1,407 @@
+// Port of the memetic algorithm MA-MMDP of Lai et al. (2020), from
+// resources/lai/MAMMDP_FV2.cpp, on top of our data structures.
+
+use crate::greedy;
+use crate::instance::Instance;
+use crate::options::{Crossover, Options};
+use crate::profile::Profile;
+use crate::solution::Solution;
+use crate::util::ReservoirSampling;
+use crate::util::Timer;
+use crate::vlns;
+
+/// Tabu tenure schedule of Lai's one-flip tabu search: tenures are
+/// TENURE_MAX * SCHEDULE[p] / 8, each one held for 5 times its length.
+const SCHEDULE: [usize; 15] = [1, 2, 1, 4, 1, 2, 1, 8, 1, 2, 1, 4, 1, 2, 1];
+const TENURE_MAX: usize = 120;
+
+/// Best move of a neighborhood scan, with ties broken uniformly at random
+struct BestMove {
+  i: usize,
+  delta: f64,
+  rs: ReservoirSampling,
+}
+
+impl BestMove {
+  fn new(n: usize) -> BestMove {
+    BestMove {
+      i: n,
+      delta: f64::MIN,
+      rs: ReservoirSampling::new(),
+    }
+  }
+
+  fn consider(&mut self, i: usize, delta: f64) {
+    if delta > self.delta {
+      self.i = i;
+      self.delta = delta;
+      self.rs = ReservoirSampling::new();
+      self.rs.consider();
+    } else if delta == self.delta && self.rs.consider() {
+      self.i = i;
+    }
+  }
+}
+
+/// Lai et al.'s one-flip tabu search, with the potentials kept in 's.cost'.
+/// Stops after 'depth' consecutive non-improving iterations, and leaves the
+/// best solution found in 's'.
+pub fn one_flip_ts(inst: &Instance, s: &mut Solution, depth: usize) {
+  let mut has = s.has.clone();
+  let mut cost = s.cost.clone();
+  let mut total_cost = s.total_cost;
+  let mut len = s.len;
+  let mut tabu = vec![0usize; inst.n]; // iteration until which a vertex is tabu
+  let mut best_obj = s.obj();
+  let (mut p, mut t) = (0, 0); // position in the tenure schedule
+  let mut iter = 0;
+  let mut non_improve = 0;
+
+  while non_improve < depth {
+    let obj = total_cost / len as f64;
+    let mut free = BestMove::new(inst.n);
+    let mut tabu_move = BestMove::new(inst.n);
+    for i in 0..inst.n {
+      let delta = if has[i] {
+        if len <= 1 {
+          continue; // can't remove all vertices
+        }
+        (obj - cost[i]) / (len as f64 - 1.0)
+      } else {
+        (cost[i] - obj) / (len as f64 + 1.0)
+      };
+      if tabu[i] <= iter {
+        free.consider(i, delta);
+      } else {
+        tabu_move.consider(i, delta);
+      }
+    }
+
+    // aspiration: a tabu move is taken if it improves the best solution
+    let mv = if free.i == inst.n
+      || (tabu_move.i < inst.n
+        && tabu_move.delta > free.delta
+        && obj + tabu_move.delta > best_obj)
+    {
+      tabu_move
+    } else {
+      free
+    };
+    if mv.i == inst.n {
+      break; // no moves at all
+    }
+
+    if has[mv.i] {
+      Solution::remove_shadow(
+        mv.i,
+        inst,
+        &mut has,
+        &mut cost,
+        &mut total_cost,
+        &mut len,
+      );
+    } else {
+      Solution::add_shadow(
+        mv.i,
+        inst,
+        &mut has,
+        &mut cost,
+        &mut total_cost,
+        &mut len,
+      );
+    }
+    tabu[mv.i] = iter + TENURE_MAX * SCHEDULE[p] / 8 + fastrand::usize(0..3);
+    t += 1;
+    if t > 5 * TENURE_MAX * SCHEDULE[p] / 8 {
+      p = (p + 1) % SCHEDULE.len();
+      t = 0;
+    }
+    iter += 1;
+
+    let obj = total_cost / len as f64;
+    if gr!(obj, best_obj) {
+      best_obj = obj;
+      s.has.copy_from_slice(&has);
+      s.cost.copy_from_slice(&cost);
+      s.total_cost = total_cost;
+      s.len = len;
+      non_improve = 0;
+    } else {
+      non_improve += 1;
+    }
+  }
+  s.v = (0..inst.n).filter(|x| s.has[*x]).collect();
+  assert_eq!(s.v.len(), s.len);
+}
+
+/// Creates a solution where each vertex is included with probability 1/2
+fn random_solution(inst: &Instance) -> Solution<'_> {
+  let mut s = Solution::new(inst);
+  for i in 0..inst.n {
+    if fastrand::bool() {
+      s.add(i);
+    }
+  }
+  while s.len < 2.min(inst.n) {
+    let i = fastrand::usize(0..inst.n);
+    if !s.has[i] {
+      s.add(i);
+    }
+  }
+  s
+}
+
+/// Uniform crossover: each vertex is inherited from a random parent
+fn random_crossover<'a>(
+  inst: &'a Instance,
+  a: &Solution<'a>,
+  b: &Solution<'a>,
+) -> Solution<'a> {
+  let mut s = Solution::new(inst);
+  for i in 0..inst.n {
+    if (if fastrand::bool() {
+      a
+    } else {
+      b
+    })
+    .has[i]
+    {
+      s.add(i);
+    }
+  }
+  if s.len < 2 {
+    s = a.clone();
+  }
+  s
+}
+
+/// Lai's greedy crossover: keeps the vertices common to both parents, then
+/// alternately adds the best vertex exclusive to each parent, until reaching
+/// the average size of the parents.
+fn greedy_crossover<'a>(
+  inst: &'a Instance,
+  a: &Solution<'a>,
+  b: &Solution<'a>,
+) -> Solution<'a> {
+  let mut s = Solution::new(inst);
+  for i in 0..inst.n {
+    if a.has[i] && b.has[i] {
+      s.add(i);
+    }
+  }
+  let target = (a.len + b.len) / 2;
+  let mut only = [a.len - s.len, b.len - s.len];
+  while s.len < target && only.iter().any(|x| *x > 0) {
+    for (k, p) in [a, b].iter().enumerate() {
+      if only[k] == 0 || s.len >= target {
+        continue;
+      }
+      // all candidates have the same denominator, so take the max potential
+      let mut best = inst.n;
+      for i in 0..inst.n {
+        if p.has[i] && !s.has[i] && (best == inst.n || s.cost[i] > s.cost[best])
+        {
+          best = i;
+        }
+      }
+      s.add(best);
+      only[k] -= 1;
+    }
+  }
+  s
+}
+
+/// Keeps the vertices common to both parents, then fills the solution greedily
+/// up to the average size of the parents.
+fn common_crossover<'a>(
+  inst: &'a Instance,
+  a: &Solution<'a>,
+  b: &Solution<'a>,
+) -> Solution<'a> {
+  let mut s = Solution::new(inst);
+  for i in 0..inst.n {
+    if a.has[i] && b.has[i] {
+      s.add(i);
+    }
+  }
+  let target = (a.len + b.len) / 2;
+  for i in greedy::insertion_order(inst, &s, target.max(2) - s.len, 0.0, None) {
+    s.add(i);
+  }
+  s
+}
+
+/// Pairs of population members that have not been recombined yet
+struct PairSet {
+  open: Vec<Vec<bool>>, // open[i][j], i < j
+}
+
+impl PairSet {
+  fn new(sz: usize) -> PairSet {
+    PairSet {
+      open: (0..sz).map(|i| (0..sz).map(|j| i < j).collect()).collect(),
+    }
+  }
+
+  /// Picks a random open pair and closes it
+  fn pick(&mut self) -> Option<(usize, usize)> {
+    let sz = self.open.len();
+    let pairs: Vec<(usize, usize)> = (0..sz)
+      .flat_map(|i| (0..sz).map(move |j| (i, j)))
+      .filter(|(i, j)| self.open[*i][*j])
+      .collect();
+    if pairs.is_empty() {
+      return None;
+    }
+    let (i, j) = pairs[fastrand::usize(0..pairs.len())];
+    self.open[i][j] = false;
+    Some((i, j))
+  }
+
+  /// Reopens all pairs involving member 'k'
+  fn reopen(&mut self, k: usize) {
+    for i in 0..self.open.len() {
+      if i < k {
+        self.open[i][k] = true;
+      } else if i > k {
+        self.open[k][i] = true;
+      }
+    }
+  }
+}
+
+/// Returns the position of the worst member of the population
+fn worst_member(pop: &[Solution]) -> Option<usize> {
+  (0..pop.len())
+    .min_by(|&i, &j| pop[i].obj().partial_cmp(&pop[j].obj()).unwrap())
+}
+
+/// Replaces the worst member of the population by 's', if 's' is better and
+/// is not a duplicate. Returns the position of 's' in the population.
+fn update_pool<'a>(pop: &mut [Solution<'a>], s: Solution<'a>) -> Option<usize> {
+  if pop.iter().any(|x| x.has == s.has) {
+    return None;
+  }
+  let worst = worst_member(pop)?;
+  if s.better(&pop[worst]) {
+    pop[worst] = s;
+    return Some(worst);
+  }
+  None
+}
+
+/// Population loop shared by the memetic algorithms: builds a population
+/// with 'new' and 'improve', recombines all pairs of members, and rebuilds the
+/// population (keeping the best solution) once all pairs have been tried.
+/// Returns (solution,profile)
+fn evolve<'a>(
+  inst: &'a Instance,
+  opt: &Options,
+  timer: &Timer,
+  crossover: Crossover,
+  new: impl Fn() -> Solution<'a>,
+  mut improve: impl FnMut(&mut Solution<'a>),
+) -> (Solution<'a>, Profile) {
+  let mut profile = Profile::new();
+  let mut best = Solution::new(inst); // solution to be returned
+  let mut pop: Vec<Solution> = Vec::with_capacity(opt.ma_pop_size);
+  let mut generation: usize = 0;
+
+  macro_rules! consider_best {
+    ($s:expr) => {{
+      if best.consider($s) {
+        profile.record(timer.elapsed().as_secs_f64(), best.obj());
+        if opt.verbose >= 1 {
+          println!("(!!!) found new best: {:.2} sz {}", best.obj(), best.len);
+        }
+      }
+    }};
+  }
+
+  while !timer.timed_out() {
+    // (re)build the population; after the first one, keep the best solution
+    pop.clear();
+    while pop.len() < opt.ma_pop_size && !timer.timed_out() {
+      let mut s = new();
+      improve(&mut s);
+      consider_best!(&s);
+      pop.push(s);
+    }
+    if timer.timed_out() {
+      break;
+    }
+    if generation > 0 {
+      if let Some(worst) = worst_member(&pop) {
+        pop[worst] = best.clone();
+      }
+    }
+    generation += 1;
+    if opt.verbose >= 1 {
+      println!("#{} population best {:.2}", generation, best.obj());
+    }
+
+    // recombine until all pairs have been tried
+    let mut pairs = PairSet::new(pop.len());
+    while let Some((i, j)) = pairs.pick() {
+      if timer.timed_out() {
+        break;
+      }
+      let mut child = match crossover {
+        Crossover::Random => random_crossover(inst, &pop[i], &pop[j]),
+        Crossover::Greedy => greedy_crossover(inst, &pop[i], &pop[j]),
+        Crossover::Common => common_crossover(inst, &pop[i], &pop[j]),
+      };
+      improve(&mut child);
+      consider_best!(&child);
+      if opt.verbose >= 2 {
+        println!(
+          "#{} cross {} {} child {:.2} sz {}",
+          generation,
+          i,
+          j,
+          child.obj(),
+          child.len
+        );
+      }
+      if let Some(k) = update_pool(&mut pop, child) {
+        pairs.reopen(k);
+      }
+    }
+  }
+  (best, profile)
+}
+
+/// Runs the memetic algorithm of Lai et al. (2020)
+/// Returns (solution,profile)
+pub fn memetic<'a>(
+  inst: &'a Instance,
+  opt: &Options,
+) -> (Solution<'a>, Profile) {
+  let timer = Timer::new(opt.time_limit);
+  evolve(
+    inst,
+    opt,
+    &timer,
+    opt.ma_crossover.unwrap_or(Crossover::Random),
+    || random_solution(inst),
+    |s| one_flip_ts(inst, s, opt.ma_ts_depth),
+  )
+}
+
+/// Runs the hybrid memetic algorithm, which improves each member with a
+/// bounded number of VLNS iterations instead of tabu search
+/// Returns (solution,profile)
+pub fn hybrid<'a>(
+  inst: &'a Instance,
+  opt: &Options,
+) -> (Solution<'a>, Profile) {
+  let timer = Timer::new(opt.time_limit);
+  evolve(
+    inst,
+    opt,
+    &timer,
+    opt.ma_crossover.unwrap_or(Crossover::Common),
+    || vlns::initial_solution(inst, fastrand::usize(0..inst.n), opt),
+    |s| vlns::vlns_descent(inst, s, opt.hybrid_vlns_iters, opt, &timer),
+  )
+}
@@ -0,0 +1,432 @@
+// Dispersion objectives besides the max-mean (--objective): the
+// fixed-cardinality max-sum (MDP), max-min, max-minsum and min-diff problems.
+// Their size is kept at --cardinality by swap moves. The hooks of Objective
+// keep the state from which they are evaluated up to date in Solution, order
+// the vertices of the greedy insertion and removal, and collapse the fixed
+// vertices of the VLNS subproblems (vlns::create_subinstance): into one vertex
+// for the sums and the max-min, as for the mean, while the max-minsum and the
+// min-diff, which depend on the cost of each fixed vertex, keep them all.
+
+use crate::greedy;
+use crate::instance::Instance;
+use crate::options::{Objective, Options};
+use crate::solution::Solution;
+use crate::util::{TabuConfig, TabuList};
+
+/// The two smallest distances from each vertex to the others of a solution,
+/// kept for the max-min objective, which evaluate its swaps in O(|s|)
+#[derive(Clone)]
+pub struct Nearest {
+  pub first: Vec<(f64, usize)>, // smallest distance, and to which vertex
+  pub second: Vec<(f64, usize)>,
+}
+
+impl Nearest {
+  /// No nearest vertices, for 'n' vertices
+  pub fn new(n: usize) -> Nearest {
+    Nearest {
+      first: vec![(f64::MAX, n); n],
+      second: vec![(f64::MAX, n); n],
+    }
+  }
+
+  /// Nearest vertices of every vertex, from scratch in O(n |s|)
+  pub fn of(s: &Solution) -> Nearest {
+    let mut near = Nearest::new(s.n());
+    for x in 0..s.n() {
+      near.compute(s, x);
+    }
+    near
+  }
+
+  /// Finds the two smallest distances from 'x' to the others of 's'
+  fn compute(&mut self, s: &Solution, x: usize) {
+    self.first[x] = (f64::MAX, s.n());
+    self.second[x] = (f64::MAX, s.n());
+    for &w in s.v.iter().filter(|w| **w != x) {
+      self.insert(x, w, s.dist(x, w));
+    }
+  }
+
+  /// Accounts for 'w', at distance 'd', among the vertices near 'x'
+  fn insert(&mut self, x: usize, w: usize, d: f64) {
+    if d < self.first[x].0 {
+      self.second[x] = self.first[x];
+      self.first[x] = (d, w);
+    } else if d < self.second[x].0 {
+      self.second[x] = (d, w);
+    }
+  }
+
+  /// Smallest distance from 'x' to the solution without 'u'
+  fn without(&self, x: usize, u: usize) -> f64 {
+    if self.first[x].1 == u {
+      self.second[x].0
+    } else {
+      self.first[x].0
+    }
+  }
+
+  /// Smallest distance from 'x' to the vertices of 's' out of 'skip', by the
+  /// nearest ones if they aren't skipped, else in O(|s|)
+  fn among(&self, s: &Solution, x: usize, skip: &[bool]) -> f64 {
+    for (d, w) in [self.first[x], self.second[x]] {
+      if w == s.n() {
+        return f64::MAX; // no other vertex
+      }
+      if !skip[w] {
+        return d;
+      }
+    }
+    s.v
+      .iter()
+      .filter(|w| **w != x && !skip[**w])
+      .map(|w| s.dist(x, *w))
+      .fold(f64::MAX, f64::min)
+  }
+}
+
+/// The fixed vertices of a VLNS subproblem, and the relaxed ones of the
+/// solution
+pub struct Core<'b> {
+  pub v: &'b [usize],
+  pub relaxed: &'b [usize],
+  pub is_relaxed: &'b [bool], // whether a vertex in [n] is in 'relaxed'
+}
+
+impl Objective {
+  /// Whether it keeps the size of solutions fixed
+  pub fn fixed(self) -> bool {
+    self != Objective::Mean
+  }
+
+  /// Whether the fixed vertices of a subproblem collapse into one vertex
+  pub fn collapses(self) -> bool {
+    matches!(self, Objective::Mean | Objective::Sum | Objective::Min)
+  }
+
+  /// Value of 's' from its state, in O(|s|); f64::MIN if it has no pair of
+  /// vertices
+  fn evaluate(self, s: &Solution) -> f64 {
+    if s.len < 2 {
+      return f64::MIN;
+    }
+    let costs = || s.v.iter().map(|u| s.cost[*u]);
+    match self {
+      Objective::Mean => s.total_cost / s.len as f64,
+      Objective::Sum => s.total_cost,
+      Objective::Min => {
+        s.v.iter().map(|u| s.near.first[*u].0).fold(f64::MAX, f64::min)
+      }
+      Objective::MinSum => costs().fold(f64::MAX, f64::min),
+      Objective::MinDiff => {
+        costs().fold(f64::MAX, f64::min) - costs().fold(f64::MIN, f64::max)
+      }
+    }
+  }
+
+  /// Updates the state of 's' after 'u' was added to it, in O(n)
+  pub fn added(self, s: &mut Solution, u: usize) {
+    if !self.fixed() {
+      return;
+    }
+    if self == Objective::Min {
+      for x in (0..s.n()).filter(|x| *x != u) {
+        let d = s.dist(x, u);
+        s.near.insert(x, u, d);
+      }
+    }
+    s.value = self.evaluate(s);
+  }
+
+  /// Updates the state of 's' after 'u' was removed from it; only the
+  /// vertices that had 'u' among their two nearest are recomputed
+  pub fn removed(self, s: &mut Solution, u: usize) {
+    if !self.fixed() {
+      return;
+    }
+    if self == Objective::Min {
+      let mut near = std::mem::replace(&mut s.near, Nearest::new(0));
+      for x in (0..s.n()).filter(|x| *x != u) {
+        if near.first[x].1 == u || near.second[x].1 == u {
+          near.compute(s, x);
+        }
+      }
+      s.near = near;
+    }
+    s.value = self.evaluate(s);
+  }
+
+  /// Recomputes the state of 's' from scratch
+  pub fn recomputed(self, s: &mut Solution) {
+    if !self.fixed() {
+      return;
+    }
+    if self == Objective::Min {
+      s.near = Nearest::of(s);
+    }
+    s.value = self.evaluate(s);
+  }
+
+  /// Value of 's' after swapping 'u', in it, for 'w', out of it; in O(1) for
+  /// the sums, else in O(|s|)
+  pub fn swap_value(self, s: &Solution, u: usize, w: usize) -> f64 {
+    let others = || s.v.iter().cloned().filter(|x| *x != u);
+    match self {
+      Objective::Mean => {
+        (s.total_cost - s.cost[u] + s.cost[w] - s.dist(u, w)) / s.len as f64
+      }
+      Objective::Sum => s.total_cost - s.cost[u] + s.cost[w] - s.dist(u, w),
+      Objective::Min => others()
+        .map(|x| s.near.without(x, u).min(s.dist(x, w)))
+        .fold(f64::MAX, f64::min),
+      Objective::MinSum | Objective::MinDiff => {
+        let cw = s.cost[w] - s.dist(w, u);
+        let (mut lo, mut hi) = (cw, cw);
+        for x in others() {
+          let c = s.cost[x] - s.dist(x, u) + s.dist(x, w);
+          lo = lo.min(c);
+          hi = hi.max(c);
+        }
+        if self == Objective::MinSum {
+          lo
+        } else {
+          lo - hi
+        }
+      }
+    }
+  }
+
+  /// How much 's' gains from having 'x', which orders the greedy insertion
+  /// and removal and the candidates of swaps: its smallest distance to the
+  /// others of 's' for max-min, the closeness of its cost to the average for
+  /// min-diff, and its cost for the others
+  pub fn attraction(self, s: &Solution, x: usize) -> f64 {
+    match self {
+      Objective::Min => s.near.first[x].0,
+      Objective::MinDiff if s.len > 0 => {
+        // the costs of the vertices of 's' add up to twice its total cost
+        -(s.cost[x] - 2.0 * s.total_cost / s.len as f64).abs()
+      }
+      _ => s.cost[x],
+    }
+  }
+
+  /// Distance from the collapsed 'core' of 's' to 'x': the sum of the
+  /// distances to its vertices, or the smallest one for max-min
+  pub fn core_dist(self, s: &Solution, core: &Core, x: usize) -> f64 {
+    match self {
+      Objective::Min => s.near.among(s, x, core.is_relaxed),
+      _ => {
+        let mut c = s.cost[x];
+        for j in core.relaxed {
+          c -= s.dist(x, *j);
+        }
+        c
+      }
+    }
+  }
+
+  /// Value among the vertices of the collapsed 'core' of 's': the sum of
+  /// their distances, or the smallest one for max-min
+  pub fn core_value(self, s: &Solution, core: &Core) -> f64 {
+    match self {
+      Objective::Min => core
+        .v
+        .iter()
+        .map(|c| s.near.among(s, *c, core.is_relaxed))
+        .fold(f64::MAX, f64::min),
+      _ => {
+        // remove the relaxed vertices' costs, adding back the edges among
+        // them, which were removed twice
+        let mut v = s.total_cost;
+        for (a, i) in core.relaxed.iter().enumerate() {
+          v -= s.cost[*i];
+          for j in &core.relaxed[..a] {
+            v += s.dist(*i, *j);
+          }
+        }
+        v
+      }
+    }
+  }
+
+  /// Value of the solution of a subproblem 't', whose first 'nfix' vertices
+  /// stand for the 'core_len' fixed ones, of value 'core_value' if collapsed
+  pub fn subproblem_value(
+    self,
+    t: &Solution,
+    nfix: usize,
+    core_len: usize,
+    core_value: f64,
+  ) -> f64 {
+    let len = t.len - nfix + core_len;
+    if self.fixed() && len < 2 {
+      return f64::MIN;
+    }
+    match self {
+      Objective::Mean => (t.total_cost + core_value) / len as f64,
+      Objective::Sum => t.total_cost + core_value,
+      Objective::Min if t.len < 2 => core_value,
+      Objective::Min => t.obj().min(core_value),
+      Objective::MinSum | Objective::MinDiff => t.obj(),
+    }
+  }
+}
+
+/// Tabu search by swap moves, which keep the size of 's', dropping and adding
+/// only the vertices of 'free'; 'cand' bounds the candidate lists of each
+/// side (0 for all). Stops after 'max_iter_wo_impr' iterations without
+/// improving, and leaves in 's' the best solution found, or with 'leave', the
+/// best one other than 's' itself, to which it then never moves back.
+pub fn swap_ts(
+  s: &mut Solution,
+  free: &[bool],
+  tabu_cfg: TabuConfig,
+  max_iter_wo_impr: usize,
+  cand: usize,
+  leave: bool,
+) {
+  let mut tabu = TabuList::new(s.n(), tabu_cfg);
+  let start_obj = s.obj();
+  // vertices of 's' out of the start solution: with 'leave', no move may
+  // bring it back to 0, else the search would stall on the start solution
+  let start = s.has.clone();
+  let mut moved = 0;
+  let mut best = (!leave).then(|| s.clone());
+  let mut best_obj = if leave {
+    f64::MIN
+  } else {
+    start_obj
+  };
+  let mut iter_w = 0;
+  while iter_w < max_iter_wo_impr {
+    iter_w += 1;
+    let key = |x: usize| s.objective.attraction(s, x);
+    let movable =
+      |i: usize| free[i] && (tabu_cfg.aspiration || !tabu.is_tabu(i));
+    let drop = greedy::candidates(
+      s.v.iter().cloned().filter(|i| movable(*i)),
+      cand,
+      |i| -key(i),
+    );
+    let add = greedy::candidates(
+      (0..s.n()).filter(|i| !s.has[*i] && movable(*i)),
+      cand,
+      key,
+    );
+
+    let mut best_move = None;
+    let mut best_score = f64::MIN;
+    for &u in &drop {
+      for &w in &add {
+        let obj = s.objective.swap_value(s, u, w);
+        if (tabu.is_tabu(u) || tabu.is_tabu(w))
+          && !tabu.aspires(obj, best_obj.max(start_obj))
+        {
+          continue;
+        }
+        if leave && moved + !start[w] as usize == !start[u] as usize {
+          continue;
+        }
+        let score = obj - tabu.penalty(u) - tabu.penalty(w);
+        if score > best_score {
+          best_score = score;
+          best_move = Some((u, w, obj));
+        }
+      }
+    }
+    tabu.advance_iter();
+    // with every move tabu, the iteration counts as non-improving
+    let (u, w, obj) = match best_move {
+      Some(m) => m,
+      None => continue,
+    };
+    s.remove(u);
+    s.add(w);
+    moved = moved + !start[w] as usize - !start[u] as usize;
+    tabu.add(u, false);
+    tabu.add(w, true);
+    if gr!(obj, best_obj) {
+      best = Some(s.clone());
+      best_obj = obj;
+      tabu.improved();
+      iter_w = 0;
+    } else {
+      tabu.stagnate();
+    }
+  }
+  if let Some(b) = best {
+    *s = b;
+  }
+}
+
+/// Creates an initial solution of size --cardinality given a seed vertex: the
+/// greedy insertion order, then swaps up to a local optimum
+pub fn initial_solution<'a>(
+  inst: &'a Instance,
+  seed_vertex: usize,
+  opt: &Options,
+) -> Solution<'a> {
+  let mut s = Solution::with_objective(inst, opt.objective);
+  s.add(seed_vertex);
+  let m = opt.cardinality.expect("No --cardinality");
+  for u in greedy::insertion_order(inst, &s, m - 1, 0.0, None) {
+    s.add(u);
+  }
+  let free = vec![true; inst.n];
+  swap_ts(&mut s, &free, TabuConfig::fixed(0), 1, opt.ts_cand, false);
+  s
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::{solution_for, tiny, value, OBJECTIVES};
+
+  #[test]
+  fn swap_value_is_value_after_swap() {
+    let inst = tiny();
+    for objective in OBJECTIVES {
+      let s = solution_for(&inst, objective, &[0, 2, 3, 5]);
+      for &u in &s.v {
+        for w in (0..inst.n).filter(|w| !s.has[*w]) {
+          let mut t = s.clone();
+          t.remove(u);
+          t.add(w);
+          let got = objective.swap_value(&s, u, w);
+          let want = value(&inst, objective, &t.v);
+          assert!(
+            (got - want).abs() < 1e-9,
+            "{:?}: swapping {} for {} gives {}, not {}",
+            objective,
+            u,
+            w,
+            want,
+            got
+          );
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn state_follows_adds_and_removes() {
+    let inst = tiny();
+    for objective in OBJECTIVES.into_iter().filter(|o| o.fixed()) {
+      let mut s = solution_for(&inst, objective, &[0, 1, 2, 3]);
+      for (u, w) in [(0, 4), (2, 5), (1, 0), (4, 2), (3, 1), (5, 6), (6, 3)] {
+        s.remove(u);
+        s.add(w);
+        assert!((s.obj() - value(&inst, objective, &s.v)).abs() < 1e-9);
+        if objective == Objective::Min {
+          let fresh = Nearest::of(&s);
+          for x in 0..inst.n {
+            assert_eq!(s.near.first[x], fresh.first[x]);
+            assert_eq!(s.near.second[x], fresh.second[x]);
+          }
+        }
+      }
+    }
+  }
+}
@@ -3,14 +3,261 @@
 // This example demonstrates clap's full 'custom derive' style of creating
 // arguments which is the simplest method of use, but sacrifices some
 // flexibility.
-pub use clap::{AppSettings, ArgEnum, Parser};
+pub use clap::{AppSettings, ArgEnum, Args, Parser, Subcommand};
+use clap::{CommandFactory, ErrorKind};
 
-#[derive(Parser, Debug)]
+/// Solution method
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Algorithm {
+  /// Our VLNS heuristic
+  Vlns,
+  /// Memetic algorithm of Lai et al. (2020)
+  Memetic,
+  /// Memetic algorithm using VLNS iterations as the improvement operator
+  Hybrid,
+}
+
+/// Objective of the dispersion problem; the variants other than the max-mean
+/// keep the size of solutions fixed to --cardinality
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Objective {
+  /// Max-mean dispersion: the mean of the distances, for any size
+  Mean,
+  /// Max-sum diversity (MDP): the sum of the distances
+  Sum,
+  /// Max-min diversity: the smallest distance
+  Min,
+  /// Max-minsum dispersion: the smallest sum of distances from a vertex to
+  /// the others
+  MinSum,
+  /// Min-diff dispersion: the difference between the largest and the
+  /// smallest sums of distances from a vertex to the others
+  MinDiff,
+}
+
+/// Solution method of the VLNS subproblems
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SubpSolver {
+  /// Tabu search, once per vertex outside the solution
+  Ts,
+  /// Exact solution with CPLEX; falls back to tabu search if not optimal
+  Exact,
+}
+
+/// Relaxation operator of the VLNS neighborhoods
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RelaxOp {
+  /// Alpha-greedy insertion and removal orders
+  Greedy,
+  /// Random vertices
+  Random,
+  /// Vertices with the largest distances to a random seed vertex
+  Related,
+  /// A cluster grown greedily from a random seed vertex
+  Cluster,
+}
+
+/// Crossover operator of the memetic algorithm
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Crossover {
+  /// Each vertex is inherited from a random parent
+  Random,
+  /// Common vertices, then the best vertices of each parent alternately
+  Greedy,
+  /// Common vertices, then the best vertices of the instance
+  Common,
+}
+
+/// Output format of result tables
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+  /// Whitespace-aligned columns, as the .dat files in test/
+  Table,
+  /// Comma-separated values
+  Csv,
+  /// One JSON object per line
+  Json,
+}
+
+/// Style of printed tables
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TableStyle {
+  Markdown,
+  Latex,
+}
+
+// Subcommands, which run many experiments instead of solving one instance
+#[derive(Subcommand, Clone, Debug)]
+pub enum Command {
+  /// Runs configurations on instances and seeds, in parallel, writing one
+  /// results table per configuration
+  Bench(BenchOptions),
+  /// Compares the results tables of algorithms: statistics per instance,
+  /// deviations from the best-known values, and Wilcoxon and Friedman tests
+  Compare(CompareOptions),
+  /// Writes the irace parameter space and default configuration, generated
+  /// from these options
+  IraceParams(IraceParamsOptions),
+  /// Runs a configuration for irace, printing its cost (the negated
+  /// objective) and time
+  #[clap(trailing_var_arg = true, allow_hyphen_values = true)]
+  TargetRunner(TargetRunnerOptions),
+  /// Tunes the options of the irace parameter space by iterated racing,
+  /// writing the elite configurations
+  Tune(TuneOptions),
+  /// Fits the table of --auto-params from bench results: the best
+  /// configuration of each instance, with its features
+  FitAuto(FitAutoOptions),
+  /// Prints statistics of instances: their distances, row sums and sizes of
+  /// greedy solutions
+  Inspect(InspectOptions),
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct CompareOptions {
+  /// Results tables, one per algorithm, as "name=file", or "file" to name the
+  /// algorithm after the file name
+  #[clap(required = true)]
+  pub tables: Vec<String>,
+  /// File with best-known values: an instance and its value in each line,
+  /// such as a registry of --bks-file
+  #[clap(long)]
+  pub bks: Option<String>,
+  /// Style of the printed tables
+  #[clap(long, arg_enum, default_value = "markdown")]
+  pub style: TableStyle,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct IraceParamsOptions {
+  /// Directory of the irace scenario, where parameters.txt and default.txt
+  /// are written
+  #[clap(short, long, default_value = "irace")]
+  pub out_dir: String,
+}
+
+/// Arguments given by irace to its target runner, after our own options
+#[derive(Args, Clone, Debug)]
+pub struct TargetRunnerOptions {
+  /// Time limit of each run, in seconds
+  #[clap(short, long, default_value = "300")]
+  pub time_limit: u64,
+  /// Whether irace passes a time bound (capping), which then limits the run
+  #[clap(long)]
+  pub capping: bool,
+  pub config_id: String,
+  pub instance_id: String,
+  pub seed: u64,
+  pub instance: String,
+  /// The bound, if --capping, and the options of the configuration
+  #[clap(allow_hyphen_values = true, multiple_values = true)]
+  pub args: Vec<String>,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct TuneOptions {
+  /// Training instance files, as a glob pattern on the file name
+  #[clap(short, long)]
+  pub instances: String,
+  /// Total number of runs
+  #[clap(short, long, default_value = "1000")]
+  pub budget: usize,
+  /// Time limit of each run, in seconds
+  #[clap(short, long, default_value = "10")]
+  pub time_limit: u64,
+  /// Number of runs in parallel
+  #[clap(short, long, default_value = "1")]
+  pub jobs: usize,
+  /// Random seed of the tuning; runs use seeds from it on
+  #[clap(short, long, default_value = "1")]
+  pub seed: u64,
+  /// Maximum number of elite configurations
+  #[clap(long, default_value = "3")]
+  pub elites: usize,
+  /// Number of instances run by a race before its first test
+  #[clap(long, default_value = "5")]
+  pub first_test: usize,
+  /// Significance level of the tests that eliminate configurations
+  #[clap(long, default_value = "0.05")]
+  pub alpha: f64,
+  /// File where the elite configurations are written, in the format of
+  /// irace/default.txt
+  #[clap(short, long, default_value = "elites.txt")]
+  pub output: String,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct FitAutoOptions {
+  /// Instance files of the bench results, as a glob pattern on the file name
+  #[clap(short, long)]
+  pub instances: String,
+  /// Configurations of the bench results, as given to bench
+  #[clap(short, long = "config", required = true)]
+  pub configs: Vec<String>,
+  /// Directory of the bench results
+  #[clap(short, long, default_value = ".")]
+  pub out_dir: String,
+  /// Format of the bench results
+  #[clap(short, long, arg_enum, default_value = "table")]
+  pub format: Format,
+  /// File where the table is written
+  #[clap(short, long, default_value = "auto-params.txt")]
+  pub table: String,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct InspectOptions {
+  /// Instance files, as a glob pattern on the file name
+  #[clap(short, long)]
+  pub instances: String,
+  /// Output format
+  #[clap(short, long, arg_enum, default_value = "table")]
+  pub format: Format,
+  /// Number of greedy solutions, from random vertices, estimating the size
+  /// of the optimal one
+  #[clap(long, default_value = "10")]
+  pub starts: usize,
+  /// Random seed of the starting vertices
+  #[clap(short, long, default_value = "1")]
+  pub seed: u64,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct BenchOptions {
+  /// Instance files, as a glob pattern on the file name (e.g. "inst/*.txt")
+  #[clap(short, long)]
+  pub instances: String,
+  /// Seeds, as a range "first-last" or a list "s1,s2,..."
+  #[clap(short, long, default_value = "1-5")]
+  pub seeds: String,
+  /// Time limit of each run, in seconds
+  #[clap(short, long, default_value = "600")]
+  pub time_limit: u64,
+  /// Memory limit of each run, in MB
+  #[clap(long, default_value = "6000")]
+  pub mem_limit: u64,
+  /// Named configuration "name=options", e.g. "sz80=--subp-sz 80"; can be
+  /// used multiple times. If not given, runs the default configuration.
+  #[clap(short, long = "config")]
+  pub configs: Vec<String>,
+  /// Number of runs in parallel
+  #[clap(short, long, default_value = "1")]
+  pub jobs: usize,
+  /// Directory of the results tables, one per configuration
+  #[clap(short, long, default_value = ".")]
+  pub out_dir: String,
+  /// Format of the results tables
+  #[clap(short, long, arg_enum, default_value = "table")]
+  pub format: Format,
+}
+
+#[derive(Parser, Clone, Debug)]
 #[clap(name = "A metaheuristic test for the Max-Mean Dispersion Problem")]
+#[clap(subcommand_negates_reqs = true, args_override_self = true)]
 pub struct Options {
   /// Input instance
-  #[clap(short, long)]
-  pub instance: String,
+  #[clap(short, long, required = true)]
+  pub instance: Option<String>,
   /// Level of verbosity; can be used multiple times
   #[clap(short, parse(from_occurrences))]
   pub verbose: usize,
@@ -27,34 +274,192 @@ pub struct Options {
   #[clap(long, default_value = "1000000")]
   pub max_iter: usize,
   /// Maximum non-improving iterations for tabu search
-  #[clap(long, default_value = "1000")] // 250
+  #[clap(long, default_value = "1000")]
   pub max_iter_wo_impr: usize,
   /// Tabu tenure
-  #[clap(long, default_value = "0")] // 5 
+  #[clap(long, default_value = "0")]
   pub tenure: usize,
+  /// Tabu tenure of dropped vertices; if not set, the same as --tenure
+  #[clap(long)]
+  pub tenure_drop: Option<usize>,
+  /// Tabu tenure of the tabu search that solves subproblems
+  #[clap(long, default_value = "1")]
+  pub ts_tenure: usize,
+  /// Maximum random extra added to each tabu tenure
+  #[clap(long, default_value = "0")]
+  pub tenure_rand: usize,
+  /// Growth of tabu tenures on each non-improving iteration; reset on
+  /// improvement
+  #[clap(long, default_value = "0")]
+  pub tenure_growth: usize,
+  /// Whether tabu moves are allowed if they improve the incumbent
+  #[clap(long, parse(try_from_str), default_value = "false")]
+  pub aspiration: bool,
+  /// Penalty on move values for frequently moved vertices (long-term memory)
+  #[clap(long, default_value = "0")]
+  pub freq_penalty: f64,
+  /// Whether the tabu search also uses swap moves, besides add/drop moves
+  #[clap(long)]
+  pub ts_swap: bool,
+  /// Size of the candidate lists of swap moves, in each side of the swap
+  #[clap(long, default_value = "10")]
+  pub ts_cand: usize,
   /// Maximum number of shakes per multistart iteration
-  #[clap(long, default_value = "25")] // 5
+  #[clap(long, default_value = "25")]
   pub max_shakes: usize,
   /// Shake size, relative to the solution size
-  #[clap(long, default_value = "0.1")] // 0.25
+  #[clap(long, default_value = "0.1")]
   pub shake_size: f64,
   /// Shake alpha parameter, higher=more aggressive shake
-  #[clap(long, default_value = "0.2")] // 0.25
+  #[clap(long, default_value = "0.2")]
   pub shake_alpha: f64,
   /// Size of neighborhood subproblem (inside/outside solution sizes will be the same)
-  #[clap(long, default_value = "70")] // 30
+  #[clap(long, default_value = "70")]
   pub subp_sz: usize,
+  /// Subproblem vertices inside the solution (default: half of --subp-sz)
+  #[clap(long)]
+  pub subp_sz_in: Option<usize>,
+  /// Subproblem vertices outside the solution (default: the rest of --subp-sz)
+  #[clap(long)]
+  pub subp_sz_out: Option<usize>,
+  /// Growth of the subproblem size per iteration without improvement,
+  /// relative to its base size
+  #[clap(long, default_value = "0")]
+  pub subp_growth: f64,
+  /// Maximum subproblem size reached by --subp-growth (default: twice
+  /// --subp-sz)
+  #[clap(long)]
+  pub subp_sz_max: Option<usize>,
   /// Number of restarts when solving the neighborhood subproblem
   #[clap(long, default_value = "1")]
   pub subp_restarts: usize,
+  /// Solution method of the neighborhood subproblems
+  #[clap(long, arg_enum, default_value = "ts")]
+  pub subp_solver: SubpSolver,
+  /// Time limit of each exact subproblem solve, in seconds
+  #[clap(long, default_value = "10")]
+  pub subp_time_limit: f64,
   /// Alpha parameter for selecting neighborhood subproblem to solve
-  #[clap(long, default_value = "0.1")] // 0.2
+  #[clap(long, default_value = "0.1")]
   pub subp_alpha: f64,
+  /// Solution method
+  #[clap(long, arg_enum, default_value = "vlns")]
+  pub algorithm: Algorithm,
+  /// Objective; all are maximized, so min-diff is reported negated
+  #[clap(long, arg_enum, default_value = "mean")]
+  pub objective: Objective,
+  /// Size of the solutions of the objectives other than mean
+  #[clap(long)]
+  pub cardinality: Option<usize>,
+  /// Minimum size of the solutions of the mean objective
+  #[clap(long, default_value = "2")]
+  pub min_size: usize,
+  /// Maximum size of the solutions of the mean objective [default: the
+  /// number of vertices]
+  #[clap(long)]
+  pub max_size: Option<usize>,
+  /// Population size of the memetic algorithm
+  #[clap(long, default_value = "10")]
+  pub ma_pop_size: usize,
+  /// Maximum non-improving iterations of the memetic algorithm's tabu search
+  #[clap(long, default_value = "50000")]
+  pub ma_ts_depth: usize,
+  /// Crossover operator of the memetic algorithms [default: random for
+  /// memetic, common for hybrid]
+  #[clap(long, arg_enum)]
+  pub ma_crossover: Option<Crossover>,
+  /// Number of VLNS iterations applied to each member of the hybrid algorithm
+  #[clap(long, default_value = "50")]
+  pub hybrid_vlns_iters: usize,
+  /// Multistart iterations between path-relinking phases of the VLNS; 0
+  /// disables path relinking
+  #[clap(long, default_value = "0")]
+  pub pr_every: usize,
+  /// Share of the time limit left to the final path-relinking phase of
+  /// --pr-every, after the search
+  #[clap(long, default_value = "0.1")]
+  pub relink_time: f64,
+  /// Size of the elite set used by path relinking
+  #[clap(long, default_value = "10")]
+  pub elite_size: usize,
+  /// Minimum distance between elite solutions, relative to their size
+  #[clap(long, default_value = "0.1")]
+  pub elite_min_dist: f64,
+  /// Whether neighborhoods are chosen adaptively (ALNS-style), from
+  /// --alns-ops, --alns-sizes and --alns-ratios
+  #[clap(long)]
+  pub alns: bool,
+  /// Relaxation operators of the adaptive neighborhood selection
+  #[clap(
+    long,
+    arg_enum,
+    use_value_delimiter = true,
+    default_value = "greedy,random,related,cluster"
+  )]
+  pub alns_ops: Vec<RelaxOp>,
+  /// Subproblem sizes of the adaptive neighborhood selection, relative to
+  /// --subp-sz
+  #[clap(long, use_value_delimiter = true, default_value = "0.5,1,1.5")]
+  pub alns_sizes: Vec<f64>,
+  /// Shares of subproblem vertices inside the solution, for the adaptive
+  /// neighborhood selection
+  #[clap(long, use_value_delimiter = true, default_value = "0.25,0.5,0.75")]
+  pub alns_ratios: Vec<f64>,
+  /// Reaction factor of the adaptive neighborhood selection weights
+  #[clap(long, default_value = "0.1")]
+  pub alns_reaction: f64,
+  /// Target values, whose times to target are added to the summary line
+  #[clap(long, use_value_delimiter = true)]
+  pub targets: Vec<f64>,
+  /// Prints the anytime profile: the time and value of every improvement of
+  /// the best solution
+  #[clap(long)]
+  pub print_profile: bool,
+  /// Writes the events of the VLNS search, as JSON lines, to this file
+  #[clap(long)]
+  pub trace: Option<String>,
+  /// Writes the best solution to this file
+  #[clap(short, long)]
+  pub output: Option<String>,
+  /// Registry of best-known solutions: the gap to the instance's entry is
+  /// added to the summary line, and the entry is updated if beaten
+  #[clap(long)]
+  pub bks_file: Option<String>,
+  /// Directory where solutions that beat the registry are stored, as
+  /// "{instance}.sol", and referenced from their entries
+  #[clap(long, requires = "bks-file")]
+  pub bks_solutions: Option<String>,
+  /// Configuration file, in TOML ("name = value" lines) or JSON (an object),
+  /// with options by their long names; the command line overrides it, and
+  /// --no-<flag> turns off the flags it sets
+  #[clap(long)]
+  pub config: Option<String>,
+  /// Calibrated settings (brimberg-300s; see presets/), which
+  /// --config and the command line override
+  #[clap(long)]
+  pub preset: Option<String>,
+  /// Eliminates the vertices proven to be in no optimal solution, by a lower
+  /// bound from greedy solutions, before solving
+  #[clap(long)]
+  pub preprocess: bool,
+  /// Number of greedy solutions, from random vertices, whose best value is
+  /// the lower bound of --preprocess; more starts give a tighter bound, and
+  /// cost a greedy solution each
+  #[clap(long, default_value = "10")]
+  pub preprocess_starts: usize,
+  /// Chooses options by the features of the instance (size, density of
+  /// positive distances and their moments), from the table of --auto-table;
+  /// --config, --preset and the command line override them
+  #[clap(long)]
+  pub auto_params: bool,
+  /// Table of --auto-params, written by fit-auto
+  #[clap(long)]
+  pub auto_table: Option<String>,
   /// Whether we're training with irace. So, just output a value.
   #[clap(long)]
   pub irace: bool,
-  // TODO check option values for consistency
-
+  #[clap(subcommand)]
+  pub command: Option<Command>,
   // // #[clap(long, default_value = "8")]
   // // pub szin: usize,
   // /// Number of "loose" vertices of neighborhood subproblem outside the solution
@@ -69,3 +474,209 @@ pub struct Options {
   // // #[clap(arg_enum)]
   // // pub alex: Alex,
 }
+
+/// Fails with 'msg' if 'cond' doesn't hold
+macro_rules! check {
+  ($cond:expr, $($msg:tt)+) => {
+    let ok: bool = $cond;
+    if !ok {
+      return Err(format!($($msg)+));
+    }
+  };
+}
+
+impl Options {
+  /// Exits with an error message if the options are inconsistent
+  pub fn validate(&self) {
+    if let Err(msg) = self.check() {
+      Options::command().error(ErrorKind::ValueValidation, msg).exit();
+    }
+  }
+
+  /// Exits with an error message if the options are inconsistent with an
+  /// instance of 'n' vertices
+  pub fn validate_instance(&self, n: usize) {
+    if let Err(msg) = self.check_instance(n) {
+      Options::command().error(ErrorKind::ValueValidation, msg).exit();
+    }
+  }
+
+  /// Checks the values of the options, and against each other
+  pub fn check(&self) -> Result<(), String> {
+    let unit = |x: f64| (0.0..=1.0).contains(&x);
+    check!(self.time_limit > 0, "--time-limit must be positive");
+    check!(self.max_iter_wo_impr > 0, "--max-iter-wo-impr must be positive");
+    check!(unit(self.shake_size), "--shake-size must be in [0, 1]");
+    check!(unit(self.shake_alpha), "--shake-alpha must be in [0, 1]");
+    check!(unit(self.subp_alpha), "--subp-alpha must be in [0, 1]");
+    check!(
+      self.max_shakes == 0 || self.shake_size > 0.0,
+      "--max-shakes {} needs a positive --shake-size, or shakes do nothing",
+      self.max_shakes
+    );
+
+    let (sz_in, sz_out) = crate::alns::base_sizes(self);
+    check!(self.subp_sz > 0, "--subp-sz must be positive");
+    check!(
+      sz_in + sz_out > 0,
+      "--subp-sz-in and --subp-sz-out can't both be 0"
+    );
+    check!(self.subp_growth >= 0.0, "--subp-growth can't be negative");
+    if let Some(max) = self.subp_sz_max {
+      check!(
+        max >= sz_in + sz_out,
+        "--subp-sz-max {} is below the subproblem size {}",
+        max,
+        sz_in + sz_out
+      );
+    }
+    check!(self.subp_restarts > 0, "--subp-restarts must be positive");
+    check!(self.subp_time_limit > 0.0, "--subp-time-limit must be positive");
+    check!(self.freq_penalty >= 0.0, "--freq-penalty can't be negative");
+    check!(!self.ts_swap || self.ts_cand > 0, "--ts-cand must be positive");
+    check!(
+      self.ts_tenure + self.tenure_rand < sz_in + sz_out,
+      "--ts-tenure plus --tenure-rand must be below the subproblem size {}, \
+       or every move of the subproblems' tabu search is tabu",
+      sz_in + sz_out
+    );
+
+    check!(self.min_size >= 2, "--min-size must be at least 2");
+    if let Some(max) = self.max_size {
+      check!(
+        max >= self.min_size,
+        "--max-size {} is below --min-size {}",
+        max,
+        self.min_size
+      );
+    }
+    let sized = self.min_size > 2 || self.max_size.is_some();
+    if sized {
+      check!(
+        self.objective == Objective::Mean && self.algorithm == Algorithm::Vlns,
+        "--min-size and --max-size apply to --objective mean with \
+         --algorithm vlns only"
+      );
+    }
+    check!(
+      !self.auto_params || self.auto_table.is_some(),
+      "--auto-params needs --auto-table, a table written by fit-auto"
+    );
+    // the elimination of --preprocess assumes that any vertex can be removed
+    check!(
+      self.min_size == 2 || !self.preprocess,
+      "--preprocess can't be used with --min-size"
+    );
+    check!(self.preprocess_starts > 0, "--preprocess-starts must be positive");
+
+    if self.objective == Objective::Mean {
+      check!(
+        self.cardinality.is_none(),
+        "--cardinality applies to the objectives other than mean only"
+      );
+    } else {
+      let name = self.objective.to_possible_value().unwrap().get_name();
+      check!(
+        self.cardinality.is_some_and(|m| m >= 2),
+        "--objective {} needs a --cardinality of at least 2",
+        name
+      );
+      check!(
+        self.algorithm == Algorithm::Vlns
+          && self.subp_solver == SubpSolver::Ts
+          && self.pr_every == 0
+          && !self.preprocess,
+        "--objective {} is solved by --algorithm vlns with --subp-solver ts \
+         only, without --pr-every or --preprocess",
+        name
+      );
+    }
+
+    check!(self.ma_pop_size >= 2, "--ma-pop-size must be at least 2");
+    check!(self.ma_ts_depth > 0, "--ma-ts-depth must be positive");
+    if self.pr_every > 0 {
+      check!(self.elite_size >= 2, "--elite-size must be at least 2");
+    }
+    check!(
+      (0.0..1.0).contains(&self.relink_time),
+      "--relink-time must be in [0, 1)"
+    );
+    check!(unit(self.elite_min_dist), "--elite-min-dist must be in [0, 1]");
+
+    if self.alns {
+      check!(!self.alns_ops.is_empty(), "--alns-ops can't be empty");
+      check!(!self.alns_sizes.is_empty(), "--alns-sizes can't be empty");
+      check!(!self.alns_ratios.is_empty(), "--alns-ratios can't be empty");
+    }
+    check!(
+      self.alns_sizes.iter().all(|x| *x > 0.0),
+      "--alns-sizes must be positive"
+    );
+    check!(
+      self.alns_ratios.iter().all(|x| unit(*x)),
+      "--alns-ratios must be in [0, 1]"
+    );
+    check!(
+      self.alns_reaction > 0.0 && self.alns_reaction <= 1.0,
+      "--alns-reaction must be in (0, 1]"
+    );
+    Ok(())
+  }
+
+  /// File of the input instance, which is given unless with a subcommand
+  pub fn instance_file(&self) -> &str {
+    self.instance.as_deref().expect("No --instance")
+  }
+
+  /// Bounds (min, max) of the size of solutions, which are fixed by
+  /// --cardinality
+  pub fn size_bounds(&self) -> (usize, usize) {
+    match self.cardinality {
+      Some(m) => (m, m),
+      None => (self.min_size, self.max_size.unwrap_or(usize::MAX)),
+    }
+  }
+
+  /// Checks the options against an instance of 'n' vertices
+  pub fn check_instance(&self, n: usize) -> Result<(), String> {
+    check!(n >= 2, "The instance must have at least 2 vertices, not {}", n);
+    let tenure = self.tenure.max(self.tenure_drop.unwrap_or(self.tenure));
+    check!(
+      tenure + self.tenure_rand < n,
+      "--tenure and --tenure-drop plus --tenure-rand must be below the {} \
+       vertices of the instance, or every move is tabu",
+      n
+    );
+    if let Some(sz_in) = self.subp_sz_in {
+      check!(
+        sz_in < n,
+        "--subp-sz-in {} must be below the {} vertices of the instance",
+        sz_in,
+        n
+      );
+    }
+    check!(
+      self.min_size <= n,
+      "--min-size {} exceeds the {} vertices of the instance",
+      self.min_size,
+      n
+    );
+    if let Some(m) = self.cardinality {
+      check!(
+        m <= n,
+        "--cardinality {} exceeds the {} vertices of the instance",
+        m,
+        n
+      );
+    }
+    if self.pr_every > 0 {
+      check!(
+        self.elite_size <= n,
+        "--elite-size {} exceeds the {} vertices of the instance",
+        self.elite_size,
+        n
+      );
+    }
+    Ok(())
+  }
+}
@@ -0,0 +1,126 @@
+// Problem reduction (--preprocess): vertices proven out of every optimal
+// solution are eliminated before the search. In an optimal solution S with
+// value f*, removing any vertex v doesn't improve it, so the sum of the
+// distances from v to the rest of S is at least f*. Thus a vertex whose
+// positive distances to the remaining vertices sum to less than a lower bound
+// of f* is in no optimal solution, and eliminating it lowers the sums of the
+// others, which may be eliminated in turn. The reduced instance is solved, and
+// its solution is mapped back to the vertices of the original one.
+//
+// Only eliminations are done. The symmetric test, fixing in a vertex whose
+// negative distances sum to more than f*, can only apply when f* is negative:
+// no vertex can be fixed once some solution has a positive mean.
+
+use crate::instance::Instance;
+use crate::options::Options;
+use crate::profile::Profile;
+use crate::solution::Solution;
+use crate::util::Timer;
+use crate::vlns;
+
+/// A reduced instance, with the original vertex of each of its vertices
+pub struct Reduction {
+  pub inst: Instance,
+  pub map: Vec<usize>,
+  pub lb: f64, // lower bound used
+}
+
+/// The best of --preprocess-starts greedy solutions of 'inst', by greedy::ts
+pub fn lower_bound<'a>(inst: &'a Instance, opt: &Options) -> Solution<'a> {
+  let mut best = Solution::new(inst);
+  for _ in 0..opt.preprocess_starts.min(inst.n) {
+    let s = vlns::initial_solution(inst, fastrand::usize(0..inst.n), opt);
+    best.consider(&s);
+  }
+  best
+}
+
+/// Eliminates the vertices of 'inst' whose positive distances to the
+/// remaining vertices sum to less than 'lb', until there are none, in O(n^2)
+pub fn reduce(inst: &Instance, lb: f64) -> Reduction {
+  let mut alive = vec![true; inst.n];
+  let mut pos: Vec<f64> = (0..inst.n)
+    .map(|u| (0..inst.n).map(|v| inst.dist(u, v).max(0.0)).sum())
+    .collect();
+  let mut queue: Vec<usize> =
+    (0..inst.n).filter(|&u| gr!(lb, pos[u])).collect();
+  for &u in &queue {
+    alive[u] = false;
+  }
+  while let Some(u) = queue.pop() {
+    for v in 0..inst.n {
+      if !alive[v] {
+        continue;
+      }
+      pos[v] -= inst.dist(u, v).max(0.0);
+      if gr!(lb, pos[v]) {
+        alive[v] = false;
+        queue.push(v);
+      }
+    }
+  }
+
+  let map: Vec<usize> = (0..inst.n).filter(|&u| alive[u]).collect();
+  let n = map.len();
+  let mut d = vec![0.0; n * n];
+  for (a, &u) in map.iter().enumerate() {
+    for (b, &v) in map.iter().enumerate() {
+      d[a * n + b] = inst.dist(u, v);
+    }
+  }
+  Reduction {
+    inst: Instance::new(n, d),
+    map,
+    lb,
+  }
+}
+
+/// Solves 'inst' after eliminating the vertices in no optimal solution, and
+/// reports how many were eliminated. Returns (solution,profile), in the
+/// vertices and time of the original instance.
+pub fn solve<'a>(inst: &'a Instance, opt: &Options) -> (Solution<'a>, Profile) {
+  let timer = Timer::new(opt.time_limit);
+  let greedy = lower_bound(inst, opt);
+  let r = reduce(inst, greedy.obj());
+  let elapsed = timer.elapsed().as_secs_f64();
+  if !opt.irace {
+    println!(
+      "preprocess n={} eliminated={} lb={:.4} time={:.4}",
+      inst.n,
+      inst.n - r.inst.n,
+      r.lb,
+      elapsed
+    );
+  }
+  // the search gets the seconds left of the time limit, rounded up so that
+  // a fraction of a second left isn't lost
+  let mut left = opt.clone();
+  left.time_limit = timer.secs_left_f64().ceil() as u64;
+  if r.inst.n == inst.n && left.time_limit > 0 {
+    let (s, mut profile) = vlns::solve_algorithm(inst, &left);
+    for p in &mut profile.points {
+      p.0 += elapsed;
+    }
+    return (s, profile);
+  }
+
+  let mut s = greedy;
+  let mut profile = Profile::new();
+  profile.record(elapsed, s.obj());
+  // with no vertex outside of the greedy solution, there's nothing to search
+  if r.inst.n > s.len && left.time_limit > 0 {
+    let (rs, rprofile) = vlns::solve_algorithm(&r.inst, &left);
+    let mut mapped = Solution::new(inst);
+    for &u in &rs.v {
+      mapped.add(r.map[u]);
+    }
+    if s.consider(&mapped) {
+      for &(t, value) in &rprofile.points {
+        if gr!(value, profile.points.last().unwrap().1) {
+          profile.record(elapsed + t, value);
+        }
+      }
+    }
+  }
+  (s, profile)
+}
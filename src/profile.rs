@@ -0,0 +1,52 @@
+// Anytime profile of a run: the staircase of (time, best value) of every
+// improvement of the best solution.
+
+/// Improvements of the best solution, in order of time
+#[derive(Default)]
+pub struct Profile {
+  pub points: Vec<(f64, f64)>, // (seconds, value)
+}
+
+impl Profile {
+  pub fn new() -> Profile {
+    Profile::default()
+  }
+
+  /// Records that the best value became 'value' at time 't'
+  pub fn record(&mut self, t: f64, value: f64) {
+    self.points.push((t, value));
+  }
+
+  /// Time to the best solution
+  pub fn ttb(&self) -> f64 {
+    self.points.last().map_or(0.0, |p| p.0)
+  }
+
+  /// Time to reach a value of at least 'target', if reached
+  pub fn ttt(&self, target: f64) -> Option<f64> {
+    self.points.iter().find(|p| !le!(p.1, target)).map(|p| p.0)
+  }
+
+  /// Primal integral up to time 'end': the integral of the primal gap to
+  /// 'reference' over time, where the gap of value v is
+  /// |reference - v| / max(|reference|, |v|), and 1 before the first solution
+  pub fn primal_integral(&self, end: f64, reference: f64) -> f64 {
+    let gap = |v: f64| {
+      let m = reference.abs().max(v.abs());
+      if m == 0.0 {
+        0.0
+      } else {
+        ((reference - v).abs() / m).min(1.0)
+      }
+    };
+    let mut integral = 0.0;
+    let (mut t, mut g) = (0.0, 1.0);
+    for (pt, pv) in &self.points {
+      let pt = pt.min(end);
+      integral += (pt - t) * g;
+      t = pt;
+      g = gap(*pv);
+    }
+    integral + (end - t).max(0.0) * g
+  }
+}
@@ -0,0 +1,184 @@
+// Elite set of diverse solutions, kept across the multistart iterations, and
+// path relinking between its members.
+
+use crate::greedy;
+use crate::instance::Instance;
+use crate::options::Options;
+use crate::solution::Solution;
+use crate::util::{TabuConfig, Timer};
+
+/// Number of vertices in exactly one of 'a' and 'b'
+fn distance(a: &Solution, b: &Solution) -> usize {
+  a.has.iter().zip(&b.has).filter(|(x, y)| x != y).count()
+}
+
+/// Set of high-quality solutions that differ from each other by at least
+/// --elite-min-dist (relative to the size of the larger solution)
+pub struct EliteSet<'a> {
+  pub pool: Vec<Solution<'a>>,
+  cap: usize,
+  min_dist: f64,
+}
+
+impl<'a> EliteSet<'a> {
+  pub fn new(opt: &Options) -> EliteSet<'a> {
+    EliteSet {
+      pool: Vec::with_capacity(opt.elite_size),
+      cap: opt.elite_size,
+      min_dist: opt.elite_min_dist,
+    }
+  }
+
+  fn close(&self, a: &Solution, b: &Solution) -> bool {
+    (distance(a, b) as f64) < self.min_dist * a.len.max(b.len) as f64
+  }
+
+  /// Inserts 's' if it is better than all members close to it (replacing the
+  /// closest one), or else if there is room or it is better than the worst
+  /// member. Returns whether 's' was inserted.
+  pub fn consider(&mut self, s: &Solution<'a>) -> bool {
+    if self.cap == 0 || s.len == 0 || self.pool.iter().any(|x| x.has == s.has) {
+      return false;
+    }
+    let close: Vec<usize> =
+      (0..self.pool.len()).filter(|&i| self.close(&self.pool[i], s)).collect();
+    if !close.is_empty() {
+      if close.iter().any(|&i| !s.better(&self.pool[i])) {
+        return false;
+      }
+      let closest =
+        *close.iter().min_by_key(|&&i| distance(&self.pool[i], s)).unwrap();
+      self.pool[closest] = s.clone();
+      return true;
+    }
+    if self.pool.len() < self.cap {
+      self.pool.push(s.clone());
+      return true;
+    }
+    let worst = (0..self.pool.len())
+      .min_by(|&i, &j| {
+        self.pool[i].obj().partial_cmp(&self.pool[j].obj()).unwrap()
+      })
+      .unwrap();
+    if s.better(&self.pool[worst]) {
+      self.pool[worst] = s.clone();
+      return true;
+    }
+    false
+  }
+}
+
+/// Walks from 'from' toward 'to', taking at each step the best add/drop move
+/// among the vertices where they differ. Returns the best intermediate
+/// solution, polished with local search, or None if the solutions are too
+/// close to have one.
+pub fn relink<'a>(
+  inst: &'a Instance,
+  from: &Solution<'a>,
+  to: &Solution<'a>,
+  opt: &Options,
+) -> Option<Solution<'a>> {
+  let mut diff: Vec<usize> =
+    (0..inst.n).filter(|&i| from.has[i] != to.has[i]).collect();
+  let mut s = from.clone();
+  let mut best: Option<Solution> = None;
+
+  // stop one move short of 'to', so that all solutions are intermediate
+  while diff.len() > 1 {
+    let obj = s.obj();
+    let mut k_best = diff.len();
+    let mut delta_best = f64::MIN;
+    for (k, &i) in diff.iter().enumerate() {
+      let delta = if s.has[i] {
+        if s.len <= 1 {
+          continue; // can't remove all vertices
+        }
+        (obj - s.cost[i]) / (s.len as f64 - 1.0)
+      } else {
+        (s.cost[i] - obj) / (s.len as f64 + 1.0)
+      };
+      if delta > delta_best {
+        k_best = k;
+        delta_best = delta;
+      }
+    }
+    if k_best == diff.len() {
+      break;
+    }
+    let i = diff.swap_remove(k_best);
+    if s.has[i] {
+      Solution::remove_shadow(
+        i,
+        inst,
+        &mut s.has,
+        &mut s.cost,
+        &mut s.total_cost,
+        &mut s.len,
+      );
+    } else {
+      Solution::add_shadow(
+        i,
+        inst,
+        &mut s.has,
+        &mut s.cost,
+        &mut s.total_cost,
+        &mut s.len,
+      );
+    }
+    if best.as_ref().is_none_or(|b| s.better(b)) {
+      best = Some(s.clone());
+    }
+  }
+
+  let mut best = best?;
+  best.v = (0..inst.n).filter(|&i| best.has[i]).collect();
+  greedy::ts(
+    inst,
+    &mut best,
+    TabuConfig::fixed(0),
+    0,
+    greedy::Neighborhood::from_options(opt),
+    None,
+    None,
+  );
+  best.recompute_from_v();
+  Some(best)
+}
+
+/// Relinks every pair of elite solutions, starting from the better one of each
+/// pair, and offers the results to the elite set. Stops early if 'timer' times
+/// out. Returns the best solution found, if any.
+pub fn relink_elite<'a>(
+  inst: &'a Instance,
+  elite: &mut EliteSet<'a>,
+  opt: &Options,
+  timer: &Timer,
+) -> Option<Solution<'a>> {
+  let mut found: Vec<Solution> = Vec::new();
+  let sz = elite.pool.len();
+  'pairs: for i in 0..sz {
+    for j in (i + 1)..sz {
+      if timer.timed_out() {
+        break 'pairs;
+      }
+      let (a, b) = (&elite.pool[i], &elite.pool[j]);
+      let (from, to) = if a.better(b) {
+        (a, b)
+      } else {
+        (b, a)
+      };
+      if let Some(s) = relink(inst, from, to, opt) {
+        found.push(s);
+      }
+    }
+  }
+
+  let mut best: Option<Solution> = None;
+  for s in found {
+    elite.consider(&s);
+    if best.as_ref().is_none_or(|b| s.better(b)) {
+      best = Some(s);
+    }
+  }
+  best
+}
@@ -1,4 +1,6 @@
 use crate::instance::Instance;
+use crate::objective::Nearest;
+use crate::options::Objective;
 
 #[derive(Clone)]
 pub struct Solution<'a> {
@@ -7,24 +9,41 @@ pub struct Solution<'a> {
   pub total_cost: f64, // objective values
   pub cost: Vec<f64>, // for each vertex in [n], the sum of distances to all vertices in v
   pub has: Vec<bool>,
+  pub objective: Objective,
+  pub near: Nearest, // for the max-min objective, kept by the Objective hooks
+  pub value: f64,    // objective value for the objectives other than mean
   inst: &'a Instance,
 }
 
 impl Solution<'_> {
-  pub fn new(inst: &Instance) -> Solution {
+  pub fn new(inst: &Instance) -> Solution<'_> {
+    Solution::with_objective(inst, Objective::Mean)
+  }
+
+  /// Empty solution, valued by 'objective'
+  pub fn with_objective(inst: &Instance, objective: Objective) -> Solution<'_> {
     Solution {
       len: 0,
       total_cost: 0.0,
       v: Vec::with_capacity(inst.n),
       cost: vec![0.0; inst.n],
       has: vec![false; inst.n],
+      objective,
+      near: Nearest::new(if objective == Objective::Min {
+        inst.n
+      } else {
+        0
+      }),
+      value: f64::MIN,
       inst,
     }
   }
 
   // Accessors
   pub fn obj(&self) -> f64 {
-    if self.len == 0 {
+    if self.objective.fixed() {
+      self.value
+    } else if self.len == 0 {
       0.0
     } else {
       self.total_cost / self.len as f64
@@ -51,10 +70,8 @@ impl Solution<'_> {
       *len += 1;
       *has.get_unchecked_mut(u) = true;
       *total_cost += cost.get_unchecked(u);
-      for w in 0..inst.n {
-        *cost.get_unchecked_mut(w) += inst.dist(u, w);
-      }
     }
+    inst.add_row(u, cost);
   }
 
   /// Removes vertex 'u' and updates data structures, but doesn't add to 'v'.
@@ -70,10 +87,8 @@ impl Solution<'_> {
       *len -= 1;
       *has.get_unchecked_mut(u) = false;
       *total_cost -= cost.get_unchecked(u);
-      for w in 0..inst.n {
-        *cost.get_unchecked_mut(w) -= inst.dist(u, w);
-      }
     }
+    inst.sub_row(u, cost);
   }
 
   /// Adds a vertex to the solution
@@ -87,6 +102,22 @@ impl Solution<'_> {
       &mut self.total_cost,
       &mut self.len,
     );
+    self.objective.added(self, u);
+  }
+
+  /// Removes a vertex from the solution
+  pub fn remove(&mut self, u: usize) {
+    let p = self.v.iter().position(|x| *x == u).unwrap();
+    self.v.swap_remove(p);
+    Self::remove_shadow(
+      u,
+      self.inst,
+      &mut self.has,
+      &mut self.cost,
+      &mut self.total_cost,
+      &mut self.len,
+    );
+    self.objective.removed(self, u);
   }
 
   /// Recomputes everything of a solution based only on `v`
@@ -108,6 +139,7 @@ impl Solution<'_> {
       );
     });
     assert_eq!(self.len, self.v.len());
+    self.objective.recomputed(self);
   }
 
   pub fn better(&self, s: &Self) -> bool {
@@ -129,4 +161,23 @@ impl Solution<'_> {
     }
     false
   }
+
+  /// Writes the solution to a file: a line with the instance name, value and
+  /// size, then a line with its vertices, numbered from 1 as in the instance
+  pub fn write(&self, path: &str, instance_name: &str) {
+    let mut v = self.v.clone();
+    v.sort_unstable();
+    let v: Vec<String> = v.iter().map(|u| (u + 1).to_string()).collect();
+    std::fs::write(
+      path,
+      format!(
+        "{} {:.6} {}\n{}\n",
+        instance_name,
+        self.obj(),
+        self.len,
+        v.join(" ")
+      ),
+    )
+    .expect("Unable to write solution");
+  }
 }
@@ -0,0 +1,302 @@
+// Descriptive statistics and the nonparametric tests used to compare
+// algorithms: Wilcoxon signed-rank and Friedman.
+
+pub fn mean(x: &[f64]) -> f64 {
+  x.iter().sum::<f64>() / x.len() as f64
+}
+
+/// Sample standard deviation; 0 with fewer than two values
+pub fn std_dev(x: &[f64]) -> f64 {
+  if x.len() < 2 {
+    return 0.0;
+  }
+  let m = mean(x);
+  (x.iter().map(|v| (v - m) * (v - m)).sum::<f64>() / (x.len() - 1) as f64)
+    .sqrt()
+}
+
+/// Ranks of 'x' in increasing order, starting at 1, with ties (within EPS)
+/// given their average rank
+pub fn ranks(x: &[f64]) -> Vec<f64> {
+  let mut idx: Vec<usize> = (0..x.len()).collect();
+  idx.sort_by(|&i, &j| x[i].partial_cmp(&x[j]).unwrap());
+  let mut r = vec![0.0; x.len()];
+  let mut a = 0;
+  while a < idx.len() {
+    let mut b = a + 1;
+    while b < idx.len() && eq!(x[idx[b]], x[idx[a]]) {
+      b += 1;
+    }
+    let avg = (a + b + 1) as f64 / 2.0; // average of ranks a+1..=b
+    for &i in &idx[a..b] {
+      r[i] = avg;
+    }
+    a = b;
+  }
+  r
+}
+
+/// Sum of t^3 - t over the groups of t tied values of 'r'
+fn ties(r: &[f64]) -> f64 {
+  let mut s = r.to_vec();
+  s.sort_by(|a, b| a.partial_cmp(b).unwrap());
+  let mut sum = 0.0;
+  let mut a = 0;
+  while a < s.len() {
+    let b = a + s[a..].iter().take_while(|v| **v == s[a]).count();
+    let t = (b - a) as f64;
+    sum += t * t * t - t;
+    a = b;
+  }
+  sum
+}
+
+/// Natural logarithm of the gamma function (Lanczos approximation)
+fn ln_gamma(x: f64) -> f64 {
+  const C: [f64; 6] = [
+    76.18009172947146,
+    -86.50532032941677,
+    24.01409824083091,
+    -1.231739572450155,
+    0.1208650973866179e-2,
+    -0.5395239384953e-5,
+  ];
+  let tmp = x + 5.5 - (x + 0.5) * (x + 5.5).ln();
+  let mut ser = 1.000000000190015;
+  for (j, c) in C.iter().enumerate() {
+    ser += c / (x + 1.0 + j as f64);
+  }
+  -tmp + (2.5066282746310005 * ser / x).ln()
+}
+
+/// Regularized upper incomplete gamma function Q(a, x)
+pub fn gamma_q(a: f64, x: f64) -> f64 {
+  const ITERS: usize = 1000;
+  const TOL: f64 = 1e-15;
+  if x <= 0.0 {
+    return 1.0;
+  }
+  let front = (-x + a * x.ln() - ln_gamma(a)).exp();
+  if x < a + 1.0 {
+    // series of P(a, x)
+    let (mut ap, mut del) = (a, 1.0 / a);
+    let mut sum = del;
+    for _ in 0..ITERS {
+      ap += 1.0;
+      del *= x / ap;
+      sum += del;
+      if del.abs() < sum.abs() * TOL {
+        break;
+      }
+    }
+    1.0 - sum * front
+  } else {
+    // continued fraction of Q(a, x), by the modified Lentz method
+    let tiny = 1e-300;
+    let mut b = x + 1.0 - a;
+    let mut c = 1.0 / tiny;
+    let mut d = 1.0 / b;
+    let mut h = d;
+    for i in 1..ITERS {
+      let an = -(i as f64) * (i as f64 - a);
+      b += 2.0;
+      d = an * d + b;
+      if d.abs() < tiny {
+        d = tiny;
+      }
+      c = b + an / c;
+      if c.abs() < tiny {
+        c = tiny;
+      }
+      d = 1.0 / d;
+      let del = d * c;
+      h *= del;
+      if (del - 1.0).abs() < TOL {
+        break;
+      }
+    }
+    front * h
+  }
+}
+
+/// Two-sided p-value of a standard normal statistic
+pub fn normal_p(z: f64) -> f64 {
+  gamma_q(0.5, z * z / 2.0)
+}
+
+/// Survival function of the chi-squared distribution
+pub fn chi2_p(x: f64, df: f64) -> f64 {
+  gamma_q(df / 2.0, x / 2.0)
+}
+
+/// Result of a statistical test
+pub struct Test {
+  pub statistic: f64,
+  pub p: f64,
+}
+
+/// Wilcoxon signed-rank test of the paired samples 'a' and 'b', two-sided,
+/// by the normal approximation with tie and continuity corrections. The
+/// statistic is the sum of the ranks of the positive differences a - b.
+pub fn wilcoxon(a: &[f64], b: &[f64]) -> Test {
+  let d: Vec<f64> =
+    a.iter().zip(b).map(|(x, y)| x - y).filter(|d| neq!(*d, 0.0)).collect();
+  let abs: Vec<f64> = d.iter().map(|x| x.abs()).collect();
+  let r = ranks(&abs);
+  let w: f64 =
+    d.iter().zip(&r).filter(|(x, _)| **x > 0.0).map(|(_, r)| r).sum();
+  let n = d.len() as f64;
+  let m = n * (n + 1.0) / 4.0;
+  let var = n * (n + 1.0) * (2.0 * n + 1.0) / 24.0 - ties(&r) / 48.0;
+  if var <= 0.0 {
+    return Test {
+      statistic: w,
+      p: 1.0,
+    };
+  }
+  let z = (w - m - 0.5 * (w - m).signum()) / var.sqrt();
+  Test {
+    statistic: w,
+    p: normal_p(z).min(1.0),
+  }
+}
+
+/// Friedman test over 'blocks' (e.g. instances), each with one value per
+/// treatment (e.g. algorithm); the statistic is chi-squared with k-1 degrees
+/// of freedom, corrected for ties. Returns the test and the mean rank of each
+/// treatment, where rank 1 is the largest value.
+pub fn friedman(blocks: &[Vec<f64>]) -> (Test, Vec<f64>) {
+  let n = blocks.len() as f64;
+  let k = blocks.first().map_or(0, |b| b.len());
+  let mut sum_ranks = vec![0.0; k];
+  let mut sum_ties = 0.0;
+  for b in blocks {
+    let neg: Vec<f64> = b.iter().map(|v| -v).collect();
+    let r = ranks(&neg);
+    sum_ties += ties(&r);
+    for (s, x) in sum_ranks.iter_mut().zip(&r) {
+      *s += x;
+    }
+  }
+  let kf = k as f64;
+  let ss: f64 =
+    sum_ranks.iter().map(|r| (r - n * (kf + 1.0) / 2.0).powi(2)).sum();
+  let den = n * kf * (kf + 1.0) - sum_ties / (kf - 1.0);
+  let statistic = if den > 0.0 {
+    12.0 * ss / den
+  } else {
+    0.0
+  };
+  let mean_ranks = sum_ranks.iter().map(|r| r / n).collect();
+  (
+    Test {
+      statistic,
+      p: chi2_p(statistic, kf - 1.0),
+    },
+    mean_ranks,
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn assert_close(x: f64, y: f64, tol: f64) {
+    assert!((x - y).abs() <= tol * y.abs().max(1.0), "{} != {}", x, y);
+  }
+
+  #[test]
+  fn ranks_average_ties() {
+    assert_eq!(ranks(&[3.0, 1.0, 2.0, 2.0]), vec![4.0, 1.0, 2.5, 2.5]);
+    assert_eq!(ranks(&[5.0, 5.0, 5.0]), vec![2.0, 2.0, 2.0]);
+  }
+
+  #[test]
+  fn gamma_q_half_is_erfc() {
+    // Q(1/2, x) = erfc(sqrt(x)); values of erfc from the C library
+    let erfc = [
+      (0.1, 0.654720846018577),
+      (0.5, 0.31731050786291404),
+      (1.0, 0.15729920705028513),
+      (2.0, 0.045500263896358396),
+      (4.0, 0.004677734981047265),
+      (9.0, 2.2090496998585438e-05),
+    ];
+    for (x, q) in erfc {
+      assert_close(gamma_q(0.5, x) / q, 1.0, 1e-8);
+    }
+    assert_eq!(gamma_q(0.5, 0.0), 1.0);
+  }
+
+  #[test]
+  #[allow(clippy::approx_constant)] // 3.14 is a datum, not pi
+  fn wilcoxon_as_r() {
+    // wilcox.test(x, y, paired = TRUE, exact = FALSE, correct = TRUE), on
+    // the depression data of its help page
+    let x = [1.83, 0.50, 1.62, 2.48, 1.68, 1.88, 1.55, 3.06, 1.30];
+    let y = [0.878, 0.647, 0.598, 2.05, 1.06, 1.29, 1.06, 3.14, 1.29];
+    let t = wilcoxon(&x, &y);
+    assert_eq!(t.statistic, 40.0);
+    assert_close(t.p, 0.04401098401295146, 1e-8);
+
+    // with a zero difference, dropped, and tied differences
+    let a = [3.0, 5.0, 2.0, 7.0, 4.0, 6.0, 5.0, 8.0];
+    let b = [1.0, 3.0, 2.0, 5.0, 5.0, 3.0, 2.0, 6.0];
+    let t = wilcoxon(&a, &b);
+    assert_eq!(t.statistic, 27.0);
+    assert_close(t.p, 0.031110765434896177, 1e-8);
+
+    // no nonzero difference
+    assert_eq!(wilcoxon(&a, &a).p, 1.0);
+  }
+
+  #[test]
+  fn friedman_as_r() {
+    // friedman.test(RoundingTimes), of its help page: chi-squared 11.143,
+    // p-value 0.003805, with ties within blocks
+    let times = [
+      [5.40, 5.50, 5.55],
+      [5.85, 5.70, 5.75],
+      [5.20, 5.60, 5.50],
+      [5.55, 5.50, 5.40],
+      [5.90, 5.85, 5.70],
+      [5.45, 5.55, 5.60],
+      [5.40, 5.40, 5.35],
+      [5.45, 5.50, 5.35],
+      [5.25, 5.15, 5.00],
+      [5.85, 5.80, 5.70],
+      [5.25, 5.20, 5.10],
+      [5.65, 5.55, 5.45],
+      [5.60, 5.35, 5.45],
+      [5.05, 5.00, 4.95],
+      [5.50, 5.50, 5.40],
+      [5.45, 5.55, 5.50],
+      [5.55, 5.55, 5.35],
+      [5.45, 5.50, 5.55],
+      [5.50, 5.45, 5.25],
+      [5.65, 5.60, 5.40],
+      [5.70, 5.65, 5.55],
+      [6.30, 6.30, 6.25],
+    ];
+    let blocks: Vec<Vec<f64>> = times.iter().map(|b| b.to_vec()).collect();
+    let (t, mean_ranks) = friedman(&blocks);
+    assert_close(t.statistic, 11.142857142857142, 1e-9);
+    assert_close(t.p, 0.003805040775511363, 1e-8);
+    // rank 1 is the largest value
+    let expected = [35.0 / 22.0, 41.0 / 22.0, 56.0 / 22.0];
+    for (r, e) in mean_ranks.iter().zip(expected) {
+      assert_close(*r, e, 1e-12);
+    }
+
+    // small sample, with a block of all ties
+    let blocks = vec![
+      vec![1.0, 2.0, 2.0],
+      vec![3.0, 1.0, 2.0],
+      vec![2.0, 2.0, 2.0],
+      vec![1.0, 3.0, 2.0],
+    ];
+    let (t, _) = friedman(&blocks);
+    assert_close(t.statistic, 0.5454545454545454, 1e-9);
+    assert_close(t.p, 0.7613003866968737, 1e-8);
+  }
+}
@@ -0,0 +1,73 @@
+//! Fixtures shared by the unit tests
+
+use crate::instance::Instance;
+use crate::options::Objective;
+use crate::solution::Solution;
+
+/// Seven vertices with distinct distances, of both signs
+pub fn tiny() -> Instance {
+  let upper: [&[f64]; 6] = [
+    &[4.0, -2.0, 7.5, 1.0, -6.0, 2.0],
+    &[3.0, -1.5, 9.0, 2.5, -0.5],
+    &[5.5, -4.0, 6.0, 1.5],
+    &[-3.0, 8.0, -7.0],
+    &[0.5, 3.5],
+    &[-2.5],
+  ];
+  let n = 7;
+  let mut d = vec![0.0; n * n];
+  for (i, row) in upper.iter().enumerate() {
+    for (k, dij) in row.iter().enumerate() {
+      let j = i + 1 + k;
+      d[i * n + j] = *dij;
+      d[j * n + i] = *dij;
+    }
+  }
+  Instance::new(n, d)
+}
+
+/// The max-mean solution of 'inst' with vertices 'v', added in order
+pub fn solution<'a>(inst: &'a Instance, v: &[usize]) -> Solution<'a> {
+  solution_for(inst, Objective::Mean, v)
+}
+
+/// The solution of 'inst' for 'objective' with vertices 'v', added in order
+pub fn solution_for<'a>(
+  inst: &'a Instance,
+  objective: Objective,
+  v: &[usize],
+) -> Solution<'a> {
+  let mut s = Solution::with_objective(inst, objective);
+  for u in v {
+    s.add(*u);
+  }
+  s
+}
+
+/// Every objective
+pub const OBJECTIVES: [Objective; 5] = [
+  Objective::Mean,
+  Objective::Sum,
+  Objective::Min,
+  Objective::MinSum,
+  Objective::MinDiff,
+];
+
+/// Value of the vertices 'v' of 'inst' by the definition of 'objective'
+pub fn value(inst: &Instance, objective: Objective, v: &[usize]) -> f64 {
+  let pairs = || {
+    v.iter()
+      .enumerate()
+      .flat_map(move |(a, u)| v[..a].iter().map(move |w| inst.dist(*u, *w)))
+  };
+  let costs = || v.iter().map(|u| v.iter().map(|w| inst.dist(*u, *w)).sum());
+  match objective {
+    Objective::Mean => pairs().sum::<f64>() / v.len() as f64,
+    Objective::Sum => pairs().sum(),
+    Objective::Min => pairs().fold(f64::MAX, f64::min),
+    Objective::MinSum => costs().fold(f64::MAX, f64::min),
+    Objective::MinDiff => {
+      costs().fold(f64::MAX, f64::min) - costs().fold(f64::MIN, f64::max)
+    }
+  }
+}
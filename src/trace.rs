@@ -0,0 +1,191 @@
+// JSON-lines log of the search trajectory (--trace), one event per line, each
+// with the seconds elapsed since the start of the run in field "t". Only the
+// VLNS (--algorithm vlns) is traced: the memetic and hybrid algorithms, and
+// the VLNS descents of the latter, write no events.
+
+use crate::alns::Relaxation;
+use crate::options::Options;
+use crate::solution::Solution;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::time::Instant;
+
+pub struct Trace {
+  out: Option<BufWriter<File>>,
+  start: Instant,
+}
+
+impl Trace {
+  /// Opens the file of --trace, if given
+  pub fn new(opt: &Options) -> Trace {
+    Trace {
+      out: opt.trace.as_ref().map(|f| {
+        BufWriter::new(File::create(f).expect("Unable to create trace file"))
+      }),
+      start: Instant::now(),
+    }
+  }
+
+  /// A trace that writes nothing
+  pub fn disabled() -> Trace {
+    Trace {
+      out: None,
+      start: Instant::now(),
+    }
+  }
+
+  fn emit(&mut self, event: &str, fields: std::fmt::Arguments) {
+    let t = self.start.elapsed().as_secs_f64();
+    if let Some(out) = &mut self.out {
+      writeln!(out, "{{\"t\":{:.6},\"event\":\"{}\",{}}}", t, event, fields)
+        .expect("Unable to write trace file");
+    }
+  }
+
+  pub fn multistart_begin(
+    &mut self,
+    it: usize,
+    start: usize,
+    s: &Solution,
+    best: &Solution,
+  ) {
+    self.emit(
+      "multistart_begin",
+      format_args!(
+        "\"it\":{},\"start\":{},\"obj\":{},\"size\":{},\"best\":{}",
+        it,
+        start,
+        Num(s.obj()),
+        s.len,
+        Num(if best.len > 0 {
+          best.obj()
+        } else {
+          f64::NAN
+        })
+      ),
+    );
+  }
+
+  pub fn multistart_end(&mut self, it: usize, iters: usize, inc: &Solution) {
+    self.emit(
+      "multistart_end",
+      format_args!(
+        "\"it\":{},\"iters\":{},\"obj\":{},\"size\":{}",
+        it,
+        iters,
+        Num(inc.obj()),
+        inc.len
+      ),
+    );
+  }
+
+  #[allow(clippy::too_many_arguments)]
+  pub fn iteration(
+    &mut self,
+    it: usize,
+    inner: usize,
+    s: &Solution,
+    rlx: &Relaxation,
+    improved: bool,
+    iters_wo_impr: usize,
+    secs: f64,
+  ) {
+    self.emit(
+      "iteration",
+      format_args!(
+        "\"it\":{},\"inner\":{},\"obj\":{},\"size\":{},{},\
+         \"improved\":{},\"iters_wo_impr\":{},\"secs\":{:.6}",
+        it,
+        inner,
+        Num(s.obj()),
+        s.len,
+        neighborhood(rlx),
+        improved,
+        iters_wo_impr,
+        secs
+      ),
+    );
+  }
+
+  pub fn shake(&mut self, it: usize, shakes: usize, from: f64, s: &Solution) {
+    self.emit(
+      "shake",
+      format_args!(
+        "\"it\":{},\"shakes\":{},\"from\":{},\"obj\":{},\"size\":{}",
+        it,
+        shakes,
+        Num(from),
+        Num(s.obj()),
+        s.len
+      ),
+    );
+  }
+
+  pub fn new_incumbent(&mut self, it: usize, s: &Solution) {
+    self.emit(
+      "new_incumbent",
+      format_args!("\"it\":{},\"obj\":{},\"size\":{}", it, Num(s.obj()), s.len),
+    );
+  }
+
+  pub fn new_best(&mut self, it: usize, s: &Solution) {
+    self.emit(
+      "new_best",
+      format_args!("\"it\":{},\"obj\":{},\"size\":{}", it, Num(s.obj()), s.len),
+    );
+  }
+
+  /// A subproblem of 'n' vertices (plus the core of 'core' vertices), solved
+  /// by 'solver' into 'sols' solutions, the best one with objective 'obj'
+  #[allow(clippy::too_many_arguments)]
+  pub fn subproblem(
+    &mut self,
+    rlx: &Relaxation,
+    n: usize,
+    core: usize,
+    solver: &str,
+    sols: usize,
+    obj: f64,
+    secs: f64,
+  ) {
+    self.emit(
+      "subproblem",
+      format_args!(
+        "{},\"n\":{},\"core\":{},\"solver\":\"{}\",\"sols\":{},\
+         \"obj\":{},\"secs\":{:.6}",
+        neighborhood(rlx),
+        n,
+        core,
+        solver,
+        sols,
+        Num(obj),
+        secs
+      ),
+    );
+  }
+}
+
+/// An objective value in JSON: null if unset (f64::MIN or f64::MAX, as the
+/// best value of no solutions) or not finite
+struct Num(f64);
+
+impl fmt::Display for Num {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    if !self.0.is_finite() || self.0 == f64::MIN || self.0 == f64::MAX {
+      write!(f, "null")
+    } else {
+      write!(f, "{:.6}", self.0)
+    }
+  }
+}
+
+/// Fields describing the neighborhood of 'rlx'
+fn neighborhood(rlx: &Relaxation) -> String {
+  format!(
+    "\"op\":\"{}\",\"sz_in\":{},\"sz_out\":{}",
+    format!("{:?}", rlx.op).to_lowercase(),
+    rlx.sz_in,
+    rlx.sz_out
+  )
+}
@@ -0,0 +1,297 @@
+// Parameter tuning without R (tune subcommand), by iterated racing as in
+// irace: each iteration samples configurations of the irace parameter space
+// around the elites of the previous one, and races them on a stream of
+// instances and seeds, eliminating those that are significantly worse by the
+// Friedman test and pairwise Wilcoxon tests against the best one.
+
+use crate::bench::glob;
+use crate::instance::Instance;
+use crate::irace::{self, Tuned};
+use crate::options::TuneOptions;
+use crate::stats;
+use crate::vlns;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// A configuration: a value per tuned option, "NA" if disabled
+type Config = Vec<String>;
+
+/// Objective values of the runs done, by configuration and block
+type Cache = HashMap<(String, usize), f64>;
+
+fn bounds(t: &Tuned) -> (f64, f64) {
+  (t.values[0].parse().unwrap(), t.values[1].parse().unwrap())
+}
+
+/// A random value of the domain of 't'
+fn sample(t: &Tuned) -> String {
+  match t.kind {
+    'i' => {
+      let (a, b) = bounds(t);
+      fastrand::i64(a as i64..=b as i64).to_string()
+    }
+    'r' => {
+      let (a, b) = bounds(t);
+      format!("{:.2}", a + fastrand::f64() * (b - a))
+    }
+    _ => t.values[fastrand::usize(0..t.values.len())].clone(),
+  }
+}
+
+/// A value of the domain of 't' near 'v': within a fifth of the range of
+/// integers and reals, a neighbor of ordinals, any value of categoricals
+fn perturb(t: &Tuned, v: &str) -> String {
+  match t.kind {
+    'i' | 'r' => {
+      let (a, b) = bounds(t);
+      let x: f64 = v.parse().unwrap();
+      let y = (x + (fastrand::f64() * 2.0 - 1.0) * (b - a) / 5.0).clamp(a, b);
+      if t.kind == 'i' {
+        (y.round() as i64).to_string()
+      } else {
+        format!("{:.2}", y)
+      }
+    }
+    'o' => {
+      let k = t.values.iter().position(|x| x == v).unwrap();
+      let k = if fastrand::bool() {
+        (k + 1).min(t.values.len() - 1)
+      } else {
+        k.saturating_sub(1)
+      };
+      t.values[k].clone()
+    }
+    _ => sample(t),
+  }
+}
+
+/// Disables the options of 'c' whose conditions don't hold, and samples the
+/// ones that became enabled
+fn fix_conditions(space: &[Tuned], c: &mut Config) {
+  for i in 0..space.len() {
+    if space[i].unless.is_some_and(|(q, v)| c[q] == v) {
+      c[i] = "NA".to_string();
+    } else if c[i] == "NA" {
+      c[i] = sample(&space[i]);
+    }
+  }
+}
+
+/// New configurations, each made by perturbing some options of an elite, the
+/// better ones more often; half of them are uniformly random while there's a
+/// single elite (e.g. the default configuration, in the first iteration)
+fn new_configs(space: &[Tuned], elites: &[Config], n: usize) -> Vec<Config> {
+  let mut configs: Vec<Config> = Vec::new();
+  let mut tries = 0;
+  while configs.len() < n && tries < 100 * n {
+    tries += 1;
+    let mut c: Config = if elites.len() <= 1 && tries % 2 == 0 {
+      space.iter().map(sample).collect()
+    } else {
+      let e = &elites[fastrand::usize(0..elites.len())
+        .min(fastrand::usize(0..elites.len()))];
+      let p = (1.0 / space.len() as f64).max(0.2);
+      let mut c = e.clone();
+      for (t, v) in space.iter().zip(c.iter_mut()) {
+        if *v != "NA" && fastrand::f64() < p {
+          *v = perturb(t, v);
+        }
+      }
+      c
+    };
+    fix_conditions(space, &mut c);
+    if !elites.contains(&c) && !configs.contains(&c) {
+      configs.push(c);
+    }
+  }
+  configs
+}
+
+/// A block of a race: an instance and a seed
+struct Block {
+  instance: PathBuf,
+  seed: u64,
+}
+
+/// Runs the configurations without results on 'block' (number 'k') in a pool
+/// of workers, adding their objective values to 'cache'. Invalid
+/// configurations, e.g. with tenures above the size of the instance, aren't
+/// run and get the worst value, so that races discard them.
+fn run_block(
+  t: &TuneOptions,
+  space: &[Tuned],
+  configs: &[&Config],
+  k: usize,
+  block: &Block,
+  cache: &mut Cache,
+) {
+  let todo: Vec<&Config> = configs
+    .iter()
+    .filter(|c| !cache.contains_key(&(c.join(" "), k)))
+    .cloned()
+    .collect();
+  if todo.is_empty() {
+    return;
+  }
+  let inst = Instance::read_from_file(block.instance.to_str().unwrap());
+  let queue = Mutex::new(todo);
+  let results = Mutex::new(Vec::new());
+  std::thread::scope(|scope| {
+    for _ in 0..t.jobs.max(1) {
+      scope.spawn(|| loop {
+        let c = match queue.lock().unwrap().pop() {
+          Some(c) => c,
+          None => break,
+        };
+        let value = match irace::run_options(
+          block.instance.to_str().unwrap(),
+          block.seed,
+          t.time_limit,
+          &irace::config_args(space, c),
+        )
+        .and_then(|opt| opt.check_instance(inst.n).map(|_| opt))
+        {
+          Ok(opt) => {
+            fastrand::seed(opt.seed);
+            vlns::solve(&inst, &opt).0.obj()
+          }
+          Err(msg) => {
+            eprintln!("Invalid configuration {}: {}", c.join(" "), msg);
+            f64::MIN
+          }
+        };
+        results.lock().unwrap().push((c.join(" "), value));
+      });
+    }
+  });
+  for (c, v) in results.into_inner().unwrap() {
+    cache.insert((c, k), v);
+  }
+}
+
+/// Races 'configs' on the blocks from the first one, while at most 'budget'
+/// runs are done and, after the first test, more than 'survivors'
+/// configurations remain. Returns
+/// the remaining configurations, best first, and the number of blocks run.
+fn race(
+  t: &TuneOptions,
+  space: &[Tuned],
+  blocks: &[Block],
+  configs: Vec<Config>,
+  budget: usize,
+  survivors: usize,
+  cache: &mut Cache,
+) -> (Vec<Config>, usize) {
+  let mut alive = configs;
+  let mut used = 0;
+  let mut k = 0;
+  let values = |alive: &[Config], k: usize, cache: &Cache| -> Vec<Vec<f64>> {
+    (0..k)
+      .map(|b| alive.iter().map(|c| cache[&(c.join(" "), b)]).collect())
+      .collect()
+  };
+  while k < blocks.len() {
+    let cost =
+      alive.iter().filter(|c| !cache.contains_key(&(c.join(" "), k))).count();
+    if used + cost > budget {
+      break;
+    }
+    let refs: Vec<&Config> = alive.iter().collect();
+    run_block(t, space, &refs, k, &blocks[k], cache);
+    used += cost;
+    k += 1;
+
+    if k >= t.first_test && alive.len() > 1 {
+      let v = values(&alive, k, cache);
+      let (friedman, ranks) = stats::friedman(&v);
+      if friedman.p < t.alpha {
+        let best = (0..alive.len())
+          .min_by(|&a, &b| ranks[a].partial_cmp(&ranks[b]).unwrap())
+          .unwrap();
+        let column =
+          |j: usize| -> Vec<f64> { v.iter().map(|b| b[j]).collect() };
+        let keep: Vec<bool> = (0..alive.len())
+          .map(|j| {
+            j == best || stats::wilcoxon(&column(best), &column(j)).p >= t.alpha
+          })
+          .collect();
+        let mut j = 0;
+        alive.retain(|_| {
+          j += 1;
+          keep[j - 1]
+        });
+      }
+    }
+    if k >= t.first_test && alive.len() <= survivors {
+      break;
+    }
+  }
+
+  if k > 0 && alive.len() > 1 {
+    let (_, ranks) = stats::friedman(&values(&alive, k, cache));
+    let mut order: Vec<usize> = (0..alive.len()).collect();
+    order.sort_by(|&a, &b| ranks[a].partial_cmp(&ranks[b]).unwrap());
+    alive = order.into_iter().map(|i| alive[i].clone()).collect();
+  }
+  (alive, k)
+}
+
+/// Runs the tune subcommand
+pub fn run(t: &TuneOptions) {
+  fastrand::seed(t.seed);
+  let space = irace::space();
+  let mut instances = glob(&t.instances);
+  if instances.is_empty() {
+    panic!("No instances match \"{}\"", t.instances);
+  }
+  fastrand::shuffle(&mut instances);
+  let blocks: Vec<Block> = (0..t.budget)
+    .map(|k| Block {
+      instance: instances[k % instances.len()].clone(),
+      seed: t.seed + k as u64,
+    })
+    .collect();
+
+  // as in irace: 2 + log2(#parameters) iterations, each with a share of the
+  // remaining budget, and fewer configurations raced longer as they go on
+  let iterations = 2 + (space.len() as f64).log2() as usize;
+  let survivors = 2 + (space.len() as f64).log2() as usize;
+  let mut elites: Vec<Config> =
+    vec![space.iter().map(|p| p.default.clone()).collect()];
+  let mut cache = Cache::new();
+  let mut used = 0;
+  for it in 1.. {
+    let budget = (t.budget - used) / iterations.saturating_sub(it - 1).max(1);
+    let n = budget / (t.first_test + it.min(5));
+    if n <= elites.len() {
+      break;
+    }
+    let mut configs = elites.clone();
+    configs.extend(new_configs(&space, &elites, n - elites.len()));
+    let before = cache.len();
+    let (alive, k) =
+      race(t, &space, &blocks, configs, budget, survivors, &mut cache);
+    used += cache.len() - before;
+    if k == 0 {
+      break;
+    }
+    elites = alive.into_iter().take(t.elites.max(1)).collect();
+    println!(
+      "Iteration {}: {} configurations on {} instances; {}/{} runs; best: {}",
+      it,
+      n,
+      k,
+      used,
+      t.budget,
+      irace::config_args(&space, &elites[0]).join(" ")
+    );
+  }
+
+  let out = format!(
+    "## Elite configurations of the tune subcommand, best first\n{}",
+    irace::format_configs(&space, &elites)
+  );
+  std::fs::write(&t.output, &out).expect("Unable to write elites");
+  print!("\n{}", out);
+}
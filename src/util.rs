@@ -1,43 +1,133 @@
-/// Simple tabu list
+use crate::options::Options;
+
+/// Configuration of a tabu list's memory
+#[derive(Clone, Copy, Debug)]
+pub struct TabuConfig {
+  pub tenure_add: usize, // tenure of vertices that were added to the solution
+  pub tenure_drop: usize, // tenure of vertices that were dropped from the solution
+  pub tenure_rand: usize, // tenures get a random extra in [0, tenure_rand]
+  pub growth: usize, // tenures grow by this much on each stagnating iteration
+  pub aspiration: bool, // whether tabu moves that beat the incumbent are allowed
+  pub freq_penalty: f64, // weight of the frequency penalty on move values
+}
+
+impl TabuConfig {
+  /// Plain recency memory with a fixed tenure
+  pub fn fixed(tenure: usize) -> TabuConfig {
+    TabuConfig {
+      tenure_add: tenure,
+      tenure_drop: tenure,
+      tenure_rand: 0,
+      growth: 0,
+      aspiration: false,
+      freq_penalty: 0.0,
+    }
+  }
+
+  /// Memory of the VLNS, which selects the neighborhoods
+  pub fn vlns(opt: &Options) -> TabuConfig {
+    TabuConfig {
+      tenure_add: opt.tenure,
+      tenure_drop: opt.tenure_drop.unwrap_or(opt.tenure),
+      ..Self::ts(opt)
+    }
+  }
+
+  /// Memory of the tabu search that solves the VLNS subproblems
+  pub fn ts(opt: &Options) -> TabuConfig {
+    TabuConfig {
+      tenure_add: opt.ts_tenure,
+      tenure_drop: opt.ts_tenure,
+      tenure_rand: opt.tenure_rand,
+      growth: opt.tenure_growth,
+      aspiration: opt.aspiration,
+      freq_penalty: opt.freq_penalty,
+    }
+  }
+}
+
+/// Tabu list with recency and frequency memory
 pub struct TabuList {
-  iter: isize,
-  tenure: isize,
-  tabu: Vec<isize>,
+  iter: usize,
+  cfg: TabuConfig,
+  extra: usize,     // current dynamic extra tenure
+  tabu: Vec<usize>, // iteration until which each vertex is tabu
+  freq: Vec<usize>, // number of times each vertex was moved
 }
 
 impl TabuList {
-  pub fn new(sz: usize, tenure: usize) -> TabuList {
-    let tenure = tenure as isize;
+  pub fn new(sz: usize, cfg: TabuConfig) -> TabuList {
     TabuList {
       iter: 1,
-      tenure,
-      tabu: vec![-tenure; sz],
+      cfg,
+      extra: 0,
+      tabu: vec![0; sz],
+      freq: vec![0; sz],
     }
   }
 
   pub fn is_tabu(&self, u: usize) -> bool {
-    self.tabu[u] + self.tenure > self.iter
+    self.tabu[u] > self.iter
+  }
+
+  /// Whether a tabu move leading to 'obj' is allowed, given the incumbent's
+  /// objective value 'inc_obj'
+  pub fn aspires(&self, obj: f64, inc_obj: f64) -> bool {
+    self.cfg.aspiration && gr!(obj, inc_obj)
+  }
+
+  /// Penalty of moving 'u', proportional to how often it was moved
+  pub fn penalty(&self, u: usize) -> f64 {
+    if self.cfg.freq_penalty == 0.0 {
+      return 0.0;
+    }
+    self.cfg.freq_penalty * self.freq[u] as f64 / self.iter as f64
   }
 
-  pub fn add(&mut self, u: usize) {
-    self.tabu[u] = self.iter;
+  /// Makes 'u' tabu; 'added' tells whether it was added to or dropped from
+  /// the solution
+  pub fn add(&mut self, u: usize, added: bool) {
+    let tenure = if added {
+      self.cfg.tenure_add
+    } else {
+      self.cfg.tenure_drop
+    };
+    let rand = if self.cfg.tenure_rand > 0 {
+      fastrand::usize(0..=self.cfg.tenure_rand)
+    } else {
+      0
+    };
+    self.tabu[u] = self.iter + tenure + self.extra + rand;
+    self.freq[u] += 1;
   }
 
   pub fn remove(&mut self, u: usize) {
-    self.tabu[u] = -self.tenure;
+    self.tabu[u] = 0;
   }
 
   pub fn advance_iter(&mut self) {
     self.iter += 1;
   }
 
+  /// Grows the tenures after a non-improving iteration, up to a quarter of the
+  /// vertices
+  pub fn stagnate(&mut self) {
+    self.extra = (self.extra + self.cfg.growth).min(self.tabu.len() / 4);
+  }
+
+  /// Resets the tenures after an improving iteration
+  pub fn improved(&mut self) {
+    self.extra = 0;
+  }
+
   pub fn reset(&mut self) {
-    *self = Self::new(self.tabu.len(), self.tenure as usize);
+    *self = Self::new(self.tabu.len(), self.cfg);
   }
 }
 
 /// Struct to handle reservoir sampling.
 /// TODO can we be using ints here instead of double?
+#[derive(Default)]
 pub struct ReservoirSampling {
   num: f64,
 }
@@ -91,4 +181,9 @@ impl Timer {
     }
     self.tl - ela
   }
+
+  /// Seconds left before the time limit, with their fraction
+  pub fn secs_left_f64(&self) -> f64 {
+    (self.tl as f64 - self.elapsed().as_secs_f64()).max(0.0)
+  }
 }
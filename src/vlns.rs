@@ -0,0 +1,719 @@
+// The VLNS heuristic: neighborhoods are subinstances with a fixed core, which
+// are solved by tabu search or exactly, within a multistart with shakes.
+
+use crate::alns::{self, Alns, Relaxation};
+use crate::cpx;
+use crate::edp_model::EDPModel;
+use crate::greedy;
+use crate::instance::Instance;
+use crate::memetic;
+use crate::objective;
+use crate::options::{Algorithm, Objective, Options, SubpSolver};
+use crate::preprocess;
+use crate::profile::Profile;
+use crate::relink::{self, EliteSet};
+use crate::solution::Solution;
+use crate::trace::Trace;
+use crate::util::Timer;
+use crate::util::{TabuConfig, TabuList};
+
+/// Shakes a solution, keeping its size within 'bounds' (min, max)
+pub fn shake(
+  inst: &Instance,
+  s: &mut Solution,
+  shake_size: usize,
+  alpha: f64,
+  bounds: (usize, usize),
+) {
+  let mut in_rlx: Vec<usize> =
+    greedy::removal_order(inst, s, shake_size, alpha, None);
+
+  let mut out_rlx: Vec<usize> =
+    greedy::insertion_order(inst, s, shake_size, alpha, None);
+  // cut the orders short where the size would leave the bounds
+  let (min, max) = bounds;
+  in_rlx.truncate((s.len + out_rlx.len()).saturating_sub(min));
+  out_rlx.truncate(max.saturating_add(in_rlx.len()).saturating_sub(s.len));
+  let mut is_in = vec![false; inst.n];
+  for i in in_rlx {
+    is_in[i] = true;
+  }
+  s.v.retain(|x| !is_in[*x]);
+  s.v.append(&mut out_rlx);
+  s.recompute_from_v();
+}
+
+/// Assigns solution 'new_s' to 's', and updates the tabu list with the vertices that changed. Consumes 'new_s'
+pub fn assign_and_update_tabu<'a>(
+  inst: &Instance,
+  s: &mut Solution<'a>,
+  new_s: Solution<'a>,
+  tabu: &mut TabuList,
+) {
+  let mut vv = vec![0; inst.n]; // -1 if in s, 1 if in new_s, 0 if in both or neither
+  for i in &s.v {
+    vv[*i] -= 1;
+  }
+  for i in &new_s.v {
+    vv[*i] += 1;
+  }
+  for v in [&s.v, &new_s.v] {
+    for i in v.iter().filter(|&x| vv[*x] != 0) {
+      tabu.add(*i, vv[*i] > 0);
+    }
+  }
+  *s = new_s;
+}
+
+/// Buffers reused across calls to create_subinstance
+pub struct SubinstanceBuffers {
+  is_rlx: Vec<bool>, // whether a vertex in [inst.n] is rlx; all false between calls
+  core: Vec<usize>,  // set of fixed vertices
+  map: Vec<usize>,   // maps [new_inst.n] to [inst.n]
+  nfix: usize,       // vertices [0, nfix) of new_inst stand for the core
+  nin: usize, // vertices [nfix, nfix + nin) of new_inst are in the solution
+}
+
+impl SubinstanceBuffers {
+  pub fn new(inst: &Instance) -> SubinstanceBuffers {
+    SubinstanceBuffers {
+      is_rlx: vec![false; inst.n],
+      core: Vec::with_capacity(inst.n),
+      map: Vec::with_capacity(inst.n),
+      nfix: 0,
+      nin: 0,
+    }
+  }
+}
+
+/// Creates a subinstance with 'rlx.sz_in' vertices in the solution plus 'rlx.sz_out' vertices outisde the solution, chosen by 'rlx.op'.
+/// The core, collapsed into vertex 0 if the objective of 's' collapses it,
+/// and its distances to the relaxed vertices are derived from the state of
+/// 's' by its hooks, in O(|rlx|^2); else its vertices come first.
+pub fn create_subinstance(
+  inst: &Instance,
+  s: &Solution,
+  rlx: &Relaxation,
+  alpha: f64,
+  tabu: &TabuList,
+  buf: &mut SubinstanceBuffers,
+  core_value: &mut f64, // value of the collapsed core, e.g. its cost, not divided by size
+) -> Instance {
+  let (mut rlx, mut out_rlx) = alns::relax(inst, s, rlx, alpha, tabu);
+  assert!(out_rlx.len() <= inst.n - s.len);
+  assert!(rlx.len() <= s.len);
+  let nin = rlx.len(); // rlx[..nin] are in the solution
+  buf.nin = nin;
+
+  rlx.append(&mut out_rlx);
+
+  assert!(rlx.len() <= inst.n); // the core may be empty if all are relaxed
+  for i in &rlx[..nin] {
+    buf.is_rlx[*i] = true;
+  }
+  buf.core.clear();
+  buf.core.extend(s.v.iter().filter(|x| !buf.is_rlx[**x]));
+  let collapse = s.objective.collapses();
+  buf.map.clear();
+  if collapse {
+    buf.map.push(0); // vertex 0 is the core
+  } else {
+    buf.map.extend(&buf.core);
+  }
+  buf.nfix = buf.map.len();
+  buf.map.extend(&rlx);
+
+  let n = buf.map.len(); // num nodes in new instance
+  let mut d = vec![0.0; n * n];
+  let first = collapse as usize; // 1 if the core is vertex 0
+  for a in first..n {
+    for b in first..n {
+      d[a * n + b] = inst.dist(buf.map[a], buf.map[b]);
+    }
+  }
+  if collapse {
+    let core = objective::Core {
+      v: &buf.core,
+      relaxed: &rlx[..nin],
+      is_relaxed: &buf.is_rlx,
+    };
+    *core_value = s.objective.core_value(s, &core);
+    for (a, i) in rlx.iter().enumerate() {
+      let c = s.objective.core_dist(s, &core, *i);
+      d[a + 1] = c;
+      d[(a + 1) * n] = c;
+    }
+  }
+  for i in &rlx[..nin] {
+    buf.is_rlx[*i] = false;
+  }
+  Instance::new(n, d)
+}
+
+/// Solves a subinstance exactly, with the core (vertex 0) fixed in the solution
+/// and counting as 'core_len' vertices, each size within --min-size and
+/// --max-size in at most --subp-time-limit and the time left. Returns the best
+/// vertices of the subinstance among the sizes solved to optimality, if any.
+pub fn solve_subinstance_exact(
+  new_inst: &Instance,
+  core_len: usize,
+  core_cost: f64,
+  opt: &Options,
+  timer: &Timer,
+) -> Option<Vec<usize>> {
+  let mut model = EDPModel::new(new_inst);
+  model.fix(0, 1);
+  let mut best_obj = f64::MIN;
+  let mut best = None;
+  let (min, max) = opt.size_bounds();
+  // sizes of the subinstance, where the core counts as 'core_len' vertices
+  let min = (min + 1).saturating_sub(core_len);
+  let max = max.saturating_add(1).saturating_sub(core_len);
+  for sz in min.max(2)..=max.min(new_inst.n) {
+    if timer.timed_out() {
+      break;
+    }
+    model.set_time_limit(opt.subp_time_limit.min(timer.secs_left_f64()));
+    let res = model.solve(sz, None);
+    if res.status != cpx::Status::Optimal {
+      continue;
+    }
+    // the core counts as 'core_len' vertices in the denominator
+    let obj = (res.obj + core_cost) / (sz - 1 + core_len) as f64;
+    if gr!(obj, best_obj) {
+      if let Some(v) = model.get_sol() {
+        best_obj = obj;
+        best = Some(v);
+      }
+    }
+  }
+  best
+}
+
+/// Run one neighborhood search iteration.
+/// Returns a pair (new_s, nb_imp), where 'nb_imp' is neighborhood size that found new_s
+#[allow(clippy::too_many_arguments)]
+pub fn vlns_iter<'a>(
+  inst: &'a Instance,
+  s: &Solution<'a>,
+  rlx: Relaxation,
+  alpha: f64,
+  tabu: &TabuList,
+  opt: &Options,
+  buf: &mut SubinstanceBuffers,
+  trace: &mut Trace,
+  timer: &Timer,
+) -> (Solution<'a>, usize) {
+  let mut inc = Solution::with_objective(inst, s.objective); // solution to be returned
+  let mut inc_obj = inc.obj();
+  let mut nb_imp: usize = 0;
+
+  for tr in 0..opt.subp_restarts {
+    let rlx = Relaxation {
+      sz_in: rlx.sz_in.min(s.len),
+      sz_out: std::cmp::min(inst.n, s.len + rlx.sz_out) - s.len,
+      ..rlx
+    };
+
+    let now = Timer::new(0);
+    let mut core_value: f64 = 0.0;
+    let new_inst =
+      create_subinstance(inst, s, &rlx, alpha, tabu, buf, &mut core_value);
+    let (core, map, nfix) = (&buf.core, &buf.map, buf.nfix);
+
+    // subinstance solutions: one per forced-in out-node, the optimal one, or
+    // the one swapped from the relaxed vertices for the fixed-size objectives
+    let mut sols: Vec<Solution> = Vec::new();
+    let solver = if s.objective.fixed() {
+      let mut t = Solution::with_objective(&new_inst, s.objective);
+      (0..nfix + buf.nin).for_each(|i| t.add(i));
+      let free: Vec<bool> = (0..new_inst.n).map(|i| i >= nfix).collect();
+      let relaxed = new_inst.n - nfix;
+      objective::swap_ts(&mut t, &free, TabuConfig::ts(opt), relaxed, 0, true);
+      sols.push(t);
+      "swap"
+    } else {
+      if opt.subp_solver == SubpSolver::Exact {
+        if let Some(v) =
+          solve_subinstance_exact(&new_inst, core.len(), core_value, opt, timer)
+        {
+          let mut t = Solution::new(&new_inst);
+          v.iter().for_each(|i| t.add(*i));
+          sols.push(t);
+        }
+      }
+      if sols.is_empty() {
+        for i in (nfix + buf.nin)..new_inst.n {
+          // only for out-nodes
+          let mut t = Solution::new(&new_inst);
+          t.add(0);
+          t.add(i);
+          greedy::ts(
+            &new_inst,
+            &mut t,
+            TabuConfig::ts(opt),
+            1,
+            greedy::Neighborhood::from_options(opt),
+            Some(i),
+            Some((core, core_value)),
+          );
+          sols.push(t);
+        }
+        "ts"
+      } else {
+        "exact"
+      }
+    };
+
+    let nb_sols = sols.len();
+    let mut best_obj = f64::MIN;
+    for (i, t) in sols.into_iter().enumerate() {
+      assert!((0..nfix).all(|x| t.has[x]));
+
+      let new_len = t.len - nfix + core.len();
+      let new_obj =
+        s.objective.subproblem_value(&t, nfix, core.len(), core_value);
+      best_obj = best_obj.max(new_obj);
+
+      if opt.verbose >= 4 {
+        println!("i {} tr {} obj {:.2} sz {}", i, tr, new_obj, new_len);
+      }
+      if gr!(new_obj, inc_obj) {
+        // the vertices standing for the core are replaced by its own
+        inc.v.clear();
+        inc.v.extend(t.v.iter().filter(|x| **x >= nfix).map(|x| map[*x]));
+        inc.v.extend(core.iter());
+        // best.has and best.c will be recomputed later! but, there may be a better way to get them than recomputing
+        inc_obj = new_obj;
+        assert_eq!(inc.v.len(), new_len);
+        nb_imp = tr;
+      }
+    }
+    trace.subproblem(
+      &rlx,
+      new_inst.n - nfix,
+      core.len(),
+      solver,
+      nb_sols,
+      best_obj,
+      now.elapsed().as_secs_f64(),
+    );
+    if gr!(inc_obj, s.obj()) {
+      break; // improved s, don't do the next neighborhood
+    }
+  }
+
+  inc.recompute_from_v();
+  let o = inc_obj;
+  // with f32 distances, the distances from the core in the subinstance are
+  // rounded, so the objective found there is only as precise as they are
+  #[cfg(not(feature = "f32"))]
+  assert!(inc.len == 0 || eq!(inc.obj(), o));
+  #[cfg(feature = "f32")]
+  assert!(inc.len == 0 || (inc.obj() - o).abs() <= 1e-4 * o.abs().max(1.0));
+  (inc, nb_imp)
+}
+
+/// Improves 's' with at most 'iters' VLNS iterations, keeping the best
+/// solution found in 's'
+pub fn vlns_descent<'a>(
+  inst: &'a Instance,
+  s: &mut Solution<'a>,
+  iters: usize,
+  opt: &Options,
+  timer: &Timer,
+) {
+  let mut cur = s.clone();
+  let mut tabu = TabuList::new(inst.n, TabuConfig::vlns(opt));
+  let mut buf = SubinstanceBuffers::new(inst);
+  let mut alns = Alns::new(opt);
+  let mut trace = Trace::disabled();
+  let mut iters_wo_impr: usize = 0;
+  for _ in 0..iters {
+    if timer.timed_out() {
+      break;
+    }
+    let rlx = alns.choose().grown(iters_wo_impr, opt);
+    let (new_s, _) = vlns_iter(
+      inst,
+      &cur,
+      rlx,
+      opt.subp_alpha,
+      &tabu,
+      opt,
+      &mut buf,
+      &mut trace,
+      timer,
+    );
+    if new_s.len == 0 {
+      break; // the neighborhood had no solution with positive value
+    }
+    let improved = new_s.better(&cur);
+    assign_and_update_tabu(inst, &mut cur, new_s, &mut tabu);
+    tabu.advance_iter();
+    let improved_inc = s.consider(&cur);
+    alns.update(improved, improved_inc);
+    if improved_inc {
+      tabu.improved();
+      iters_wo_impr = 0;
+    } else {
+      tabu.stagnate();
+      iters_wo_impr += 1;
+    }
+  }
+}
+
+/// Creates an initial solution given a seed vertex
+pub fn initial_solution<'a>(
+  inst: &'a Instance,
+  seed_vertex: usize,
+  opt: &Options,
+) -> Solution<'a> {
+  if opt.objective.fixed() {
+    return objective::initial_solution(inst, seed_vertex, opt);
+  }
+  let mut s = Solution::new(inst);
+  s.add(seed_vertex);
+  // let o = greedy::insertion_order(inst, &s, inst.n, 0.25, None);
+  // for i in o {
+  //   s.
+  // }
+  greedy::ts(
+    inst,
+    &mut s,
+    TabuConfig::fixed(0),
+    0,
+    greedy::Neighborhood::from_options(opt),
+    None,
+    None,
+  );
+  s.recompute_from_v(); // TODO probably not needed
+  s
+}
+
+/// Solves 'inst' with the method of --algorithm, after --preprocess if set
+/// Returns (solution,profile)
+pub fn solve<'a>(inst: &'a Instance, opt: &Options) -> (Solution<'a>, Profile) {
+  if opt.preprocess {
+    preprocess::solve(inst, opt)
+  } else {
+    solve_algorithm(inst, opt)
+  }
+}
+
+/// Solves 'inst' with the method of --algorithm
+/// Returns (solution,profile)
+pub fn solve_algorithm<'a>(
+  inst: &'a Instance,
+  opt: &Options,
+) -> (Solution<'a>, Profile) {
+  match opt.algorithm {
+    Algorithm::Vlns => vlnsheuristic(inst, opt),
+    Algorithm::Memetic => memetic::memetic(inst, opt),
+    Algorithm::Hybrid => memetic::hybrid(inst, opt),
+  }
+}
+
+/// Runs the proposed heuristic
+/// Returns (solution,profile)
+pub fn vlnsheuristic<'a>(
+  inst: &'a Instance,
+  opt: &Options,
+) -> (Solution<'a>, Profile) {
+  // if inst.n <= SZ_MAX * 2 + 1 {
+  let timer = Timer::new(opt.time_limit);
+  if inst.n <= opt.subp_sz + 1 && opt.objective == Objective::Mean {
+    if opt.verbose >= 1 {
+      println!("Instance is small; running exact algorithm");
+    }
+    let s = exact(inst, opt);
+    let mut profile = Profile::new();
+    profile.record(timer.elapsed().as_secs_f64(), s.obj());
+    return (s, profile);
+  }
+
+  let mut profile = Profile::new();
+  // solution to be returned
+  let mut best = Solution::with_objective(inst, opt.objective);
+  let mut it_outer: usize = 0;
+
+  let mut buf = SubinstanceBuffers::new(inst);
+  let mut alns = Alns::new(opt);
+  let mut elite = EliteSet::new(opt);
+  let mut trace = Trace::new(opt);
+
+  // with path relinking, the search leaves the last --relink-time of the time
+  // limit to the final relinking phase
+  let reserved = match opt.pr_every {
+    0 => 0.0,
+    _ => opt.relink_time * opt.time_limit as f64,
+  };
+  let searching = || timer.secs_left_f64() > reserved;
+
+  let mut starts: Vec<usize> = (0..inst.n).collect();
+  fastrand::shuffle(&mut starts);
+  for start in starts {
+    let mut s = initial_solution(inst, start, opt);
+
+    if it_outer >= opt.max_iter || !searching() {
+      break;
+    }
+    it_outer += 1;
+    if opt.verbose >= 1 {
+      // no best solution yet on the first start
+      let best_obj = match best.len {
+        0 => "NA".to_string(),
+        _ => format!("{:.2}", best.obj()),
+      };
+      println!(
+        "#{} heur {:.2} sz {} start {} best {}",
+        it_outer,
+        s.obj(),
+        s.len,
+        start,
+        best_obj
+      );
+    }
+    trace.multistart_begin(it_outer, start, &s, &best);
+
+    let shake_size: usize = (s.len as f64 * opt.shake_size) as usize;
+    let mut it_inner: usize = 0;
+    let mut shakes: usize = 0;
+    let mut iters_wo_impr: usize = 0;
+    let mut inc = s.clone(); // best solution in this multistart iteration
+    let mut tabu = TabuList::new(inst.n, TabuConfig::vlns(opt));
+    // the initial solution may be the best, if no iteration improves it
+    if best.consider(&inc) {
+      profile.record(timer.elapsed().as_secs_f64(), best.obj());
+      trace.new_best(it_outer, &best);
+    }
+
+    loop {
+      if !searching() {
+        break;
+      }
+      it_inner += 1;
+      let now = Timer::new(0);
+
+      let rlx = alns.choose().grown(iters_wo_impr, opt);
+      let (new_s, nb_imp) = vlns_iter(
+        inst,
+        &s,
+        rlx,
+        opt.subp_alpha,
+        &tabu,
+        opt,
+        &mut buf,
+        &mut trace,
+        &timer,
+      );
+
+      assert!(new_s.len > 0);
+      let improved_last_s = new_s.better(&s);
+
+      // assign s
+      assign_and_update_tabu(inst, &mut s, new_s, &mut tabu);
+      tabu.advance_iter();
+
+      if opt.verbose >= 3 {
+        println!(
+        "#{}.{} sz {} obj {:.2} nb {:?} inc {:.2} iterw {} shakes {} time {}ms ",
+        it_outer,
+        it_inner,
+        s.len,
+        s.obj(),
+        nb_imp,
+        inc.obj(),
+        iters_wo_impr,
+        shakes,
+        now.elapsed().as_millis());
+      }
+      trace.iteration(
+        it_outer,
+        it_inner,
+        &s,
+        &rlx,
+        improved_last_s,
+        iters_wo_impr,
+        now.elapsed().as_secs_f64(),
+      );
+
+      let improved_inc = s.better(&inc);
+      alns.update(improved_last_s, improved_inc);
+
+      if !improved_inc {
+        // this iteration did not improve
+        tabu.stagnate();
+        if !improved_last_s {
+          iters_wo_impr += 1; //? is this right? shouldn't we increment it when !improved_inc?
+        }
+        if iters_wo_impr > opt.max_iter_wo_impr {
+          // shake
+          shakes += 1;
+          if shakes > opt.max_shakes {
+            if opt.verbose >= 1 {
+              println!(
+                "#{} ts   {:.2} sz {} iter {}",
+                it_outer,
+                inc.obj(),
+                inc.len,
+                it_inner
+              );
+            }
+            break;
+          }
+          s = inc.clone(); // start from best `outer` solution
+          shake(inst, &mut s, shake_size, opt.shake_alpha, opt.size_bounds());
+          tabu.reset();
+          iters_wo_impr = 0;
+          trace.shake(it_outer, shakes, inc.obj(), &s);
+          if opt.verbose >= 2 {
+            println!(
+              "#{} shake {} obj {:.2} -> {:.2}",
+              it_outer,
+              shakes,
+              inc.obj(),
+              s.obj()
+            );
+          }
+        }
+      } else {
+        // solution improved
+        tabu.improved();
+        iters_wo_impr = 0;
+      }
+
+      // improved local best?
+      if inc.consider(&s) {
+        trace.new_incumbent(it_outer, &inc);
+        // improved global best?
+        if best.consider(&inc) {
+          shakes = 0;
+          profile.record(timer.elapsed().as_secs_f64(), best.obj());
+          trace.new_best(it_outer, &best);
+          if opt.verbose >= 1 {
+            println!("(!!!) found new best: {:.2} sz {}", best.obj(), best.len);
+          }
+        }
+      }
+    }
+    if opt.verbose >= 1 {
+      println!();
+    }
+    trace.multistart_end(it_outer, it_inner, &inc);
+
+    if opt.pr_every > 0 {
+      elite.consider(&inc);
+      if it_outer.is_multiple_of(opt.pr_every) {
+        let pr = relink::relink_elite(inst, &mut elite, opt, &timer);
+        if let Some(pr) = pr {
+          if best.consider(&pr) {
+            profile.record(timer.elapsed().as_secs_f64(), best.obj());
+            trace.new_best(it_outer, &best);
+          }
+          if opt.verbose >= 1 {
+            println!("#{} path relinking {:.2}", it_outer, pr.obj());
+          }
+        }
+      }
+    }
+  }
+  // final path-relinking phase, within the time left, at least the reserved
+  if opt.pr_every > 0 {
+    if let Some(pr) = relink::relink_elite(inst, &mut elite, opt, &timer) {
+      if best.consider(&pr) {
+        profile.record(timer.elapsed().as_secs_f64(), best.obj());
+        trace.new_best(it_outer, &best);
+      }
+      if opt.verbose >= 1 {
+        println!("final path relinking {:.2}", pr.obj());
+      }
+    }
+  }
+  if opt.alns && !opt.irace {
+    alns.report();
+  }
+  (best, profile)
+}
+
+/// Run an exact algorithm, over the sizes within --min-size and --max-size
+pub fn exact<'a>(inst: &'a Instance, opt: &Options) -> Solution<'a> {
+  let mut model = EDPModel::new(inst);
+  let mut best = Solution::new(inst);
+  let mut best_obj = f64::MIN;
+  let (min, max) = opt.size_bounds();
+  for sz in min..=max.min(inst.n) {
+    let res = model.solve(sz, None);
+    if opt.verbose >= 1 {
+      println!("sz {}, res {:?}", sz, res);
+    }
+    let obj = res.obj / sz as f64;
+    if res.status == cpx::Status::Optimal && obj > best_obj {
+      if let Some(s) = model.get_sol() {
+        best_obj = obj;
+        best.v = s;
+        best.recompute_from_v();
+      }
+    }
+  }
+  best
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::options::RelaxOp;
+  use crate::testing::{solution_for, tiny, value, OBJECTIVES};
+
+  #[test]
+  fn subproblem_value_is_value_of_the_solution() {
+    let inst = tiny();
+    let tabu = TabuList::new(inst.n, TabuConfig::fixed(0));
+    let rlx = Relaxation {
+      op: RelaxOp::Random,
+      sz_in: 2,
+      sz_out: 2,
+    };
+    for objective in OBJECTIVES {
+      let s = solution_for(&inst, objective, &[0, 2, 3, 5]);
+      let mut buf = SubinstanceBuffers::new(&inst);
+      let mut core_value = 0.0;
+      let new_inst = create_subinstance(
+        &inst,
+        &s,
+        &rlx,
+        0.0,
+        &tabu,
+        &mut buf,
+        &mut core_value,
+      );
+      let (nfix, nin) = (buf.nfix, buf.nin);
+      assert_eq!(
+        nfix,
+        if objective.collapses() {
+          1
+        } else {
+          2
+        }
+      );
+      // swap the second relaxed vertex in the solution for the first one out
+      let mut t = Solution::with_objective(&new_inst, objective);
+      for x in (0..nfix + 1).chain([nfix + nin]) {
+        t.add(x);
+      }
+      let got =
+        objective.subproblem_value(&t, nfix, buf.core.len(), core_value);
+      let mut v: Vec<usize> =
+        t.v.iter().filter(|x| **x >= nfix).map(|x| buf.map[*x]).collect();
+      v.extend(&buf.core);
+      let want = value(&inst, objective, &v);
+      assert!(
+        (got - want).abs() < 1e-9,
+        "{:?}: {} instead of {}",
+        objective,
+        got,
+        want
+      );
+    }
+  }
+}